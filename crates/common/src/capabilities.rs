@@ -8,7 +8,12 @@ pub fn capabilities() -> models::Capabilities {
             explain: Some(models::LeafCapability {}),
             nested_fields: models::NestedFieldCapabilities {
                 aggregates: None,
-                filter_by: None,
+                filter_by: Some(models::NestedFieldFilterByCapabilities {
+                    nested_arrays: Some(models::NestedArrayFilterByCapabilities {
+                        contains: Some(models::LeafCapability {}),
+                        is_empty: Some(models::LeafCapability {}),
+                    }),
+                }),
                 order_by: None,
                 nested_collections: None,
             },
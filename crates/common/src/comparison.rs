@@ -0,0 +1,117 @@
+use crate::config::config_file::ScalarRepresentation;
+use ndc_models::{self as models, ComparisonOperatorName};
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+/// Comparison operators advertised for scalar types, and evaluated by the connector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    Eq,
+    Neq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Like,
+}
+
+impl ComparisonOperator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComparisonOperator::Eq => "_eq",
+            ComparisonOperator::Neq => "_neq",
+            ComparisonOperator::Gt => "_gt",
+            ComparisonOperator::Gte => "_gte",
+            ComparisonOperator::Lt => "_lt",
+            ComparisonOperator::Lte => "_lte",
+            ComparisonOperator::In => "_in",
+            ComparisonOperator::Like => "_like",
+        }
+    }
+    /// The operators that apply to values with the given representation.
+    /// Arbitrary JSON and GeoJSON values have no meaningful comparison, so get no operators.
+    pub fn for_representation(representation: &ScalarRepresentation) -> &'static [Self] {
+        use ComparisonOperator::{Eq, Gt, Gte, In, Like, Lt, Lte, Neq};
+        match representation {
+            ScalarRepresentation::String => &[Eq, Neq, Gt, Gte, Lt, Lte, In, Like],
+            ScalarRepresentation::Int8
+            | ScalarRepresentation::Int16
+            | ScalarRepresentation::Int32
+            | ScalarRepresentation::Int64
+            | ScalarRepresentation::Float32
+            | ScalarRepresentation::Float64
+            | ScalarRepresentation::BigInteger
+            | ScalarRepresentation::BigDecimal
+            | ScalarRepresentation::Date
+            | ScalarRepresentation::Timestamp
+            | ScalarRepresentation::TimestampTz => &[Eq, Neq, Gt, Gte, Lt, Lte, In],
+            ScalarRepresentation::Boolean
+            | ScalarRepresentation::Uuid
+            | ScalarRepresentation::Bytes
+            | ScalarRepresentation::Enum { .. } => &[Eq, Neq, In],
+            ScalarRepresentation::Geography
+            | ScalarRepresentation::Geometry
+            | ScalarRepresentation::Json => &[],
+        }
+    }
+    /// The NDC definition of this operator, for a scalar type of the given name
+    pub fn definition(
+        &self,
+        scalar_type_name: &models::TypeName,
+    ) -> models::ComparisonOperatorDefinition {
+        match self {
+            ComparisonOperator::Eq => models::ComparisonOperatorDefinition::Equal,
+            ComparisonOperator::In => models::ComparisonOperatorDefinition::In,
+            ComparisonOperator::Gt => models::ComparisonOperatorDefinition::GreaterThan,
+            ComparisonOperator::Gte => models::ComparisonOperatorDefinition::GreaterThanOrEqual,
+            ComparisonOperator::Lt => models::ComparisonOperatorDefinition::LessThan,
+            ComparisonOperator::Lte => models::ComparisonOperatorDefinition::LessThanOrEqual,
+            ComparisonOperator::Neq | ComparisonOperator::Like => {
+                models::ComparisonOperatorDefinition::Custom {
+                    argument_type: models::Type::Named {
+                        name: scalar_type_name.to_owned(),
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl Display for ComparisonOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ComparisonOperator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "_eq" => Ok(ComparisonOperator::Eq),
+            "_neq" => Ok(ComparisonOperator::Neq),
+            "_gt" => Ok(ComparisonOperator::Gt),
+            "_gte" => Ok(ComparisonOperator::Gte),
+            "_lt" => Ok(ComparisonOperator::Lt),
+            "_lte" => Ok(ComparisonOperator::Lte),
+            "_in" => Ok(ComparisonOperator::In),
+            "_like" => Ok(ComparisonOperator::Like),
+            _ => Err(format!("Unknown comparison operator {s}")),
+        }
+    }
+}
+
+pub fn comparison_operators(
+    scalar_type_name: &models::TypeName,
+    representation: &ScalarRepresentation,
+) -> BTreeMap<ComparisonOperatorName, models::ComparisonOperatorDefinition> {
+    ComparisonOperator::for_representation(representation)
+        .iter()
+        .map(|operator| {
+            (
+                operator.name().to_owned().into(),
+                operator.definition(scalar_type_name),
+            )
+        })
+        .collect()
+}
@@ -1,8 +1,19 @@
-use config_file::{RequestConfigFile, ResponseConfigFile};
+use config_file::{
+    ConnectionConfigFile, DeprecatedFilter, DriftDetectionConfigFile, FilterConfigFile,
+    NameFilterConfigFile, NameMappingConfigFile, NamingConfigFile, OperationsConfigFile,
+    RequestConfigFile, ResponseConfigFile, ResponseValidation, ScalarRepresentation,
+    SecretReloadConfigFile, ServerConfigFile,
+};
+use glob_match::glob_match;
 use ndc_models::{ArgumentName, FieldName, FunctionName, ProcedureName, ScalarTypeName, TypeName};
-use schema::SchemaDefinition;
-use std::collections::BTreeMap;
+use schema::{ObjectFieldDefinition, OperationType, SchemaDefinition, SchemaDefinitionErrors};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    time::Duration,
+};
 pub mod config_file;
+pub mod config_value;
 pub mod schema;
 
 #[derive(Debug, Clone)]
@@ -10,13 +21,81 @@ pub struct ServerConfig {
     pub connection: ConnectionConfig,
     pub request: RequestConfig,
     pub response: ResponseConfig,
+    pub naming: NamingConfig,
+    pub scalars: BTreeMap<ScalarTypeName, ScalarRepresentation>,
+    pub filter: FilterConfig,
+    pub operations: OperationsConfig,
+    pub drift_detection: Option<DriftDetectionConfig>,
+    pub secret_reload: Option<SecretReloadConfig>,
     pub schema: SchemaDefinition,
 }
 
+impl ServerConfig {
+    /// Build a schema definition for another schema document with this configuration, eg. the live upstream schema.
+    /// Representations of scalars the document does not define are ignored
+    pub fn schema_definition(
+        &self,
+        schema_document: &graphql_parser::schema::Document<'_, String>,
+    ) -> Result<SchemaDefinition, SchemaDefinitionErrors> {
+        let mut schema_config = SchemaConfig {
+            request: self.request.clone(),
+            response: self.response.clone(),
+            scalars: self.scalars.clone(),
+            filter: self.filter.clone(),
+            naming: self.naming.clone(),
+            operations: self.operations.clone(),
+        };
+        schema_config.retain_defined_scalars(schema_document);
+
+        SchemaDefinition::new(schema_document, &schema_config)
+    }
+    /// Look up the root field for a function, which may be a query or mutation field
+    pub fn function_field(&self, function: &str) -> Option<RootField<'_>> {
+        let field_name = self.naming.functions.upstream_name(function)?;
+        let (operation_type, field_definition) = self.schema.function_field(&field_name)?;
+        Some(RootField {
+            operation_type,
+            name: field_name.into(),
+            definition: field_definition,
+        })
+    }
+    /// Look up the root field for a procedure, which may be a query or mutation field
+    pub fn procedure_field(&self, procedure: &str) -> Option<RootField<'_>> {
+        let field_name = self.naming.procedures.upstream_name(procedure)?;
+        let (operation_type, field_definition) = self.schema.procedure_field(&field_name)?;
+        Some(RootField {
+            operation_type,
+            name: field_name.into(),
+            definition: field_definition,
+        })
+    }
+}
+
+/// A root field of the target schema, as the target of a function or procedure
 #[derive(Debug, Clone)]
+pub struct RootField<'a> {
+    /// The operation type the field belongs to, which determines the upstream operation to build
+    pub operation_type: OperationType,
+    /// The field name in the target schema
+    pub name: FieldName,
+    pub definition: &'a ObjectFieldDefinition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ConnectionConfig {
     pub endpoint: String,
     pub headers: BTreeMap<String, String>,
+    pub oauth2: Option<OAuth2Config>,
+}
+
+/// OAuth2 client credentials flow, see [`OAuth2ConfigFile`](config_file::OAuth2ConfigFile)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuth2Config {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+    pub audience: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +111,113 @@ pub struct ResponseConfig {
     pub type_name_prefix: String,
     pub type_name_suffix: String,
     pub forward_headers: Vec<String>,
+    pub validation: ResponseValidation,
+}
+
+/// Which root fields and types are exposed, see [`FilterConfigFile`]
+#[derive(Debug, Clone, Default)]
+pub struct FilterConfig {
+    pub queries: NameFilter,
+    pub mutations: NameFilter,
+    pub types: NameFilter,
+    pub deprecated: DeprecatedFilter,
+}
+#[derive(Debug, Clone, Default)]
+pub struct NameFilter {
+    pub include: Option<Vec<String>>,
+    pub exclude: Vec<String>,
+}
+
+/// How names in the target schema map to names in the NDC schema, see [`NamingConfigFile`]
+#[derive(Debug, Clone, Default)]
+pub struct NamingConfig {
+    pub functions: NameMapping,
+    pub procedures: NameMapping,
+    pub types: NameMapping,
+}
+#[derive(Debug, Clone, Default)]
+pub struct NameMapping {
+    pub prefix: String,
+    pub suffix: String,
+    pub rename: BTreeMap<String, String>,
+}
+
+/// Periodic comparison of the configured schema with the live upstream schema, see [`DriftDetectionConfigFile`]
+#[derive(Debug, Clone)]
+pub struct DriftDetectionConfig {
+    pub interval: Duration,
+}
+
+/// The configuration a [`SchemaDefinition`] is built with
+#[derive(Debug, Clone, Default)]
+pub struct SchemaConfig {
+    pub request: RequestConfig,
+    pub response: ResponseConfig,
+    pub scalars: BTreeMap<ScalarTypeName, ScalarRepresentation>,
+    pub filter: FilterConfig,
+    pub naming: NamingConfig,
+    pub operations: OperationsConfig,
+}
+
+impl SchemaConfig {
+    /// Drop representations of scalars the schema document does not define, eg. scalars removed from the live schema,
+    /// so the removal is reported as a schema change rather than as invalid configuration
+    pub fn retain_defined_scalars(
+        &mut self,
+        schema_document: &graphql_parser::schema::Document<'_, String>,
+    ) {
+        let defined_scalars = schema::defined_scalar_names(schema_document);
+        self.scalars
+            .retain(|name, _| defined_scalars.contains(name.as_str()));
+    }
+}
+
+impl From<&ServerConfigFile> for SchemaConfig {
+    fn from(value: &ServerConfigFile) -> Self {
+        SchemaConfig {
+            request: value.request.clone().unwrap_or_default().into(),
+            response: value.response.clone().unwrap_or_default().into(),
+            scalars: value.scalars.clone(),
+            filter: value.filter.clone().unwrap_or_default().into(),
+            naming: value.naming.clone().unwrap_or_default().into(),
+            operations: value.operations.clone().unwrap_or_default().into(),
+        }
+    }
+}
+
+/// Periodic re-reading of the connection settings, see [`SecretReloadConfigFile`]
+#[derive(Debug, Clone)]
+pub struct SecretReloadConfig {
+    pub interval: Duration,
+    /// The unresolved execution connection settings, resolved again on each reload
+    pub connection: ConnectionConfigFile,
+    /// The directory file values are relative to
+    pub configuration_dir: PathBuf,
+    /// The environment the configuration was read with, which environment values are resolved from
+    pub environment: HashMap<String, String>,
+}
+
+impl SecretReloadConfig {
+    pub fn new(
+        value: SecretReloadConfigFile,
+        connection: ConnectionConfigFile,
+        configuration_dir: PathBuf,
+        environment: HashMap<String, String>,
+    ) -> Self {
+        SecretReloadConfig {
+            interval: Duration::from_secs(value.interval_seconds.unwrap_or(60).max(1)),
+            connection,
+            configuration_dir,
+            environment,
+        }
+    }
+}
+
+/// Overrides for the NDC kind of root fields, see [`OperationsConfigFile`]
+#[derive(Debug, Clone, Default)]
+pub struct OperationsConfig {
+    pub query_fields_as_procedures: Vec<String>,
+    pub mutation_fields_as_functions: Vec<String>,
 }
 
 impl Default for RequestConfig {
@@ -52,6 +238,7 @@ impl Default for ResponseConfig {
             type_name_prefix: "_".to_owned(),
             type_name_suffix: "Response".to_owned(),
             forward_headers: vec![],
+            validation: ResponseValidation::default(),
         }
     }
 }
@@ -85,10 +272,118 @@ impl From<ResponseConfigFile> for ResponseConfig {
                 .type_name_suffix
                 .unwrap_or_else(|| Self::default().type_name_suffix),
             forward_headers: value.forward_headers.unwrap_or_default(),
+            validation: value.validation.unwrap_or_default(),
         }
     }
 }
 
+impl From<FilterConfigFile> for FilterConfig {
+    fn from(value: FilterConfigFile) -> Self {
+        FilterConfig {
+            queries: value.queries.map(Into::into).unwrap_or_default(),
+            mutations: value.mutations.map(Into::into).unwrap_or_default(),
+            types: value.types.map(Into::into).unwrap_or_default(),
+            deprecated: value.deprecated.unwrap_or_default(),
+        }
+    }
+}
+impl From<NameFilterConfigFile> for NameFilter {
+    fn from(value: NameFilterConfigFile) -> Self {
+        NameFilter {
+            include: value.include,
+            exclude: value.exclude.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<NamingConfigFile> for NamingConfig {
+    fn from(value: NamingConfigFile) -> Self {
+        NamingConfig {
+            functions: value.functions.map(Into::into).unwrap_or_default(),
+            procedures: value.procedures.map(Into::into).unwrap_or_default(),
+            types: value.types.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+impl From<NameMappingConfigFile> for NameMapping {
+    fn from(value: NameMappingConfigFile) -> Self {
+        NameMapping {
+            prefix: value.prefix.unwrap_or_default(),
+            suffix: value.suffix.unwrap_or_default(),
+            rename: value.rename,
+        }
+    }
+}
+
+impl From<DriftDetectionConfigFile> for DriftDetectionConfig {
+    fn from(value: DriftDetectionConfigFile) -> Self {
+        DriftDetectionConfig {
+            interval: Duration::from_secs(value.interval_seconds.unwrap_or(300).max(1)),
+        }
+    }
+}
+
+impl From<OperationsConfigFile> for OperationsConfig {
+    fn from(value: OperationsConfigFile) -> Self {
+        OperationsConfig {
+            query_fields_as_procedures: value.query_fields_as_procedures.unwrap_or_default(),
+            mutation_fields_as_functions: value.mutation_fields_as_functions.unwrap_or_default(),
+        }
+    }
+}
+
+impl OperationsConfig {
+    pub fn query_field_is_procedure(&self, field_name: &str) -> bool {
+        self.query_fields_as_procedures
+            .iter()
+            .any(|pattern| glob_match(pattern, field_name))
+    }
+    pub fn mutation_field_is_function(&self, field_name: &str) -> bool {
+        self.mutation_fields_as_functions
+            .iter()
+            .any(|pattern| glob_match(pattern, field_name))
+    }
+}
+
+impl NameMapping {
+    /// The NDC name for a name in the target schema
+    pub fn ndc_name(&self, upstream_name: &str) -> String {
+        match self.rename.get(upstream_name) {
+            Some(name) => name.to_owned(),
+            None => format!("{}{upstream_name}{}", self.prefix, self.suffix),
+        }
+    }
+    /// The name in the target schema for an NDC name, if it could have been produced by this mapping
+    pub fn upstream_name(&self, ndc_name: &str) -> Option<String> {
+        if let Some((upstream_name, _)) = self.rename.iter().find(|(_, name)| *name == ndc_name) {
+            return Some(upstream_name.to_owned());
+        }
+
+        let upstream_name = ndc_name
+            .strip_prefix(&self.prefix)?
+            .strip_suffix(&self.suffix)?;
+
+        // explicitly renamed names are not also available under their prefixed name
+        if self.rename.contains_key(upstream_name) {
+            None
+        } else {
+            Some(upstream_name.to_owned())
+        }
+    }
+}
+
+impl NameFilter {
+    /// Names are included if they match any include pattern, or there are none, and match no exclude pattern
+    pub fn includes(&self, name: &str) -> bool {
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|include| include.iter().any(|pattern| glob_match(pattern, name)));
+
+        included && !self.exclude.iter().any(|pattern| glob_match(pattern, name))
+    }
+}
+
 impl ResponseConfig {
     pub fn query_response_type_name(&self, query: &FunctionName) -> TypeName {
         format!(
@@ -1,4 +1,4 @@
-use ndc_models::{ArgumentName, FieldName, ScalarTypeName};
+use ndc_models::{ArgumentName, FieldName, ScalarTypeName, TypeRepresentation};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -21,6 +21,36 @@ pub struct ServerConfigFile {
     /// Optional configuration for responses.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub response: Option<ResponseConfigFile>,
+    /// Representation of each scalar type in the schema.
+    /// Scalars not listed here have their representation inferred from their name, defaulting to json.
+    /// The CLI update command adds any new scalars from the schema, using their @specifiedBy url when present.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub scalars: BTreeMap<ScalarTypeName, ScalarRepresentation>,
+    /// Optional rules for which root fields and types are exposed.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub filter: Option<FilterConfigFile>,
+    /// Optional renaming of functions, procedures and types, eg. to avoid conflicts with other connectors.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub naming: Option<NamingConfigFile>,
+    /// Optional overrides for whether root fields are exposed as functions or procedures.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub operations: Option<OperationsConfigFile>,
+    /// Optional periodic comparison of the configured schema with the live schema of the execution endpoint.
+    /// Differences are logged and reported as metrics.
+    #[serde(
+        rename = "driftDetection",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub drift_detection: Option<DriftDetectionConfigFile>,
+    /// Optional periodic re-reading of the execution endpoint and headers, so rotated secrets are used without a restart.
+    /// Environment variables keep the values the connector started with, and files are read again on each reload.
+    #[serde(
+        rename = "secretReload",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub secret_reload: Option<SecretReloadConfigFile>,
 }
 
 impl Default for ServerConfigFile {
@@ -31,6 +61,12 @@ impl Default for ServerConfigFile {
             introspection: ConnectionConfigFile::default(),
             request: None,
             response: None,
+            scalars: BTreeMap::new(),
+            filter: None,
+            naming: None,
+            operations: None,
+            drift_detection: None,
+            secret_reload: None,
         }
     }
 }
@@ -42,6 +78,25 @@ pub struct ConnectionConfigFile {
     /// Static headers to include with each request
     #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub headers: BTreeMap<String, ConfigValue>,
+    /// Optional OAuth2 client credentials flow. The access token is sent in the Authorization header,
+    /// replacing any Authorization header configured or forwarded.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub oauth2: Option<OAuth2ConfigFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2ConfigFile {
+    /// URL of the token endpoint of the authorization server
+    pub token_url: ConfigValue,
+    pub client_id: ConfigValue,
+    pub client_secret: ConfigValue,
+    /// Scopes to request, sent space-separated
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub scopes: Option<Vec<String>>,
+    /// Audience to request the token for, required by some authorization servers
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub audience: Option<String>,
 }
 
 impl Default for ConnectionConfigFile {
@@ -49,6 +104,7 @@ impl Default for ConnectionConfigFile {
         Self {
             endpoint: ConfigValue::ValueFromEnv("GRAPHQL_ENDPOINT".to_string()),
             headers: BTreeMap::new(),
+            oauth2: None,
         }
     }
 }
@@ -100,6 +156,208 @@ pub struct ResponseConfigFile {
     /// Enabling this requires additional configuration on the ddn side, see docs for more.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub forward_headers: Option<Vec<String>>,
+    /// How to handle upstream responses that do not match the schema, eg. null values for non-null fields.
+    /// Defaults to "disabled", which forwards responses as-is.
+    /// "warn" applies GraphQL null propagation to invalid values and logs a warning for each,
+    /// "error" fails the request.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub validation: Option<ResponseValidation>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterConfigFile {
+    /// Fields of the query type to expose as functions.
+    /// Defaults to all fields.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub queries: Option<NameFilterConfigFile>,
+    /// Fields of the mutation type to expose as procedures.
+    /// Defaults to all fields.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mutations: Option<NameFilterConfigFile>,
+    /// Types to expose.
+    /// Fields and optional arguments of excluded types are removed, as are root fields that require them.
+    /// Defaults to all types.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub types: Option<NameFilterConfigFile>,
+    /// Whether to expose fields, arguments and input fields marked @deprecated.
+    /// Defaults to "include", which adds the deprecation reason to their descriptions.
+    /// "exclude" removes them, unless they are required arguments or input fields.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub deprecated: Option<DeprecatedFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NameFilterConfigFile {
+    /// Names to include, defaults to all names.
+    /// Supports glob patterns eg. "user*".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub include: Option<Vec<String>>,
+    /// Names to exclude, even if included.
+    /// Supports glob patterns eg. "admin_*".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub exclude: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingConfigFile {
+    /// Naming of functions, generated from fields of the query type.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub functions: Option<NameMappingConfigFile>,
+    /// Naming of procedures, generated from fields of the mutation type.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub procedures: Option<NameMappingConfigFile>,
+    /// Naming of scalar and object types.
+    /// Does not apply to the headers type, or to response types, which have their own configuration.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub types: Option<NameMappingConfigFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NameMappingConfigFile {
+    /// Prefix added to names.
+    /// Defaults to "".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub prefix: Option<String>,
+    /// Suffix added to names.
+    /// Defaults to "".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub suffix: Option<String>,
+    /// Explicit names, keyed by the name in the target schema.
+    /// Prefix and suffix are not applied to renamed names.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub rename: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationsConfigFile {
+    /// Fields of the query type to expose as procedures, eg. side-effecting operations in RPC-style schemas.
+    /// Defaults to [], AKA all query fields are functions.
+    /// Supports glob patterns eg. "send*".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub query_fields_as_procedures: Option<Vec<String>>,
+    /// Fields of the mutation type to expose as functions, eg. read-only lookups.
+    /// Defaults to [], AKA all mutation fields are procedures.
+    /// Supports glob patterns eg. "get*".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mutation_fields_as_functions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftDetectionConfigFile {
+    /// Seconds between introspections of the execution endpoint, at least 1.
+    /// Defaults to 300.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub interval_seconds: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretReloadConfigFile {
+    /// Seconds between reloads of the execution endpoint and headers, at least 1.
+    /// Defaults to 60.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub interval_seconds: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ResponseValidation {
+    /// Forward responses as-is
+    #[default]
+    Disabled,
+    /// Null out invalid values following GraphQL null propagation rules, and log a warning for each
+    Warn,
+    /// Fail the request if the response contains invalid values
+    Error,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DeprecatedFilter {
+    /// Expose deprecated fields, with the deprecation reason in their description
+    #[default]
+    Include,
+    /// Hide deprecated fields
+    Exclude,
+}
+
+/// How values of a scalar type are represented in JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScalarRepresentation {
+    /// JSON booleans
+    Boolean,
+    /// Any JSON string
+    String,
+    /// A 8-bit signed integer with a minimum value of -2^7 and a maximum value of 2^7 - 1
+    Int8,
+    /// A 16-bit signed integer with a minimum value of -2^15 and a maximum value of 2^15 - 1
+    Int16,
+    /// A 32-bit signed integer with a minimum value of -2^31 and a maximum value of 2^31 - 1
+    Int32,
+    /// A 64-bit signed integer with a minimum value of -2^63 and a maximum value of 2^63 - 1
+    Int64,
+    /// An IEEE-754 single-precision floating-point number
+    Float32,
+    /// An IEEE-754 double-precision floating-point number
+    Float64,
+    /// Arbitrary-precision integer string
+    BigInteger,
+    /// Arbitrary-precision decimal string
+    BigDecimal,
+    /// UUID string (8-4-4-4-12)
+    Uuid,
+    /// ISO 8601 date
+    Date,
+    /// ISO 8601 timestamp
+    Timestamp,
+    /// ISO 8601 timestamp-with-timezone
+    TimestampTz,
+    /// GeoJSON, per RFC 7946
+    Geography,
+    /// GeoJSON Geometry object, per RFC 7946
+    Geometry,
+    /// Base64-encoded bytes
+    Bytes,
+    /// Arbitrary JSON
+    Json,
+    /// One of the specified string values
+    Enum {
+        #[serde(rename = "oneOf")]
+        one_of: Vec<String>,
+    },
+}
+
+impl From<ScalarRepresentation> for TypeRepresentation {
+    fn from(value: ScalarRepresentation) -> Self {
+        match value {
+            ScalarRepresentation::Boolean => TypeRepresentation::Boolean,
+            ScalarRepresentation::String => TypeRepresentation::String,
+            ScalarRepresentation::Int8 => TypeRepresentation::Int8,
+            ScalarRepresentation::Int16 => TypeRepresentation::Int16,
+            ScalarRepresentation::Int32 => TypeRepresentation::Int32,
+            ScalarRepresentation::Int64 => TypeRepresentation::Int64,
+            ScalarRepresentation::Float32 => TypeRepresentation::Float32,
+            ScalarRepresentation::Float64 => TypeRepresentation::Float64,
+            ScalarRepresentation::BigInteger => TypeRepresentation::BigInteger,
+            ScalarRepresentation::BigDecimal => TypeRepresentation::BigDecimal,
+            ScalarRepresentation::Uuid => TypeRepresentation::UUID,
+            ScalarRepresentation::Date => TypeRepresentation::Date,
+            ScalarRepresentation::Timestamp => TypeRepresentation::Timestamp,
+            ScalarRepresentation::TimestampTz => TypeRepresentation::TimestampTZ,
+            ScalarRepresentation::Geography => TypeRepresentation::Geography,
+            ScalarRepresentation::Geometry => TypeRepresentation::Geometry,
+            ScalarRepresentation::Bytes => TypeRepresentation::Bytes,
+            ScalarRepresentation::Json => TypeRepresentation::JSON,
+            ScalarRepresentation::Enum { one_of } => TypeRepresentation::Enum { one_of },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -110,6 +368,16 @@ pub enum ConfigValue {
     /// A reference to an environment variable, from which the value will be read at runtime
     #[serde(rename = "valueFromEnv")]
     ValueFromEnv(String),
+    /// A reference to an environment variable, with a default used when the variable is not set
+    #[serde(rename = "valueFromEnvOrDefault")]
+    ValueFromEnvOrDefault { variable: String, default: String },
+    /// A path to a file, relative to the configuration directory, from which the value will be read at runtime.
+    /// A trailing line break is not part of the value
+    #[serde(rename = "valueFromFile")]
+    ValueFromFile(String),
+    /// A string with environment variables interpolated at runtime, such as `Bearer {{env.TOKEN}}`
+    #[serde(rename = "valueFromTemplate")]
+    ValueFromTemplate(String),
 }
 
 impl Default for RequestConfigFile {
@@ -130,6 +398,7 @@ impl Default for ResponseConfigFile {
             type_name_prefix: None,
             type_name_suffix: None,
             forward_headers: Some(vec![]),
+            validation: None,
         }
     }
 }
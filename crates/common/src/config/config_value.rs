@@ -0,0 +1,285 @@
+use super::{
+    config_file::{ConfigValue, ConnectionConfigFile, OAuth2ConfigFile},
+    ConnectionConfig, OAuth2Config,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValueError {
+    EnvironmentVariableNotSet(String),
+    ReadFile { path: PathBuf, message: String },
+    InvalidTemplate { template: String, message: String },
+}
+
+impl Error for ConfigValueError {}
+
+impl Display for ConfigValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigValueError::EnvironmentVariableNotSet(name) => {
+                write!(f, "Environment Variable {name} not set")
+            }
+            ConfigValueError::ReadFile { path, message } => {
+                write!(f, "Could not read file {}: {message}", path.display())
+            }
+            ConfigValueError::InvalidTemplate { template, message } => {
+                write!(f, "Invalid template {template:?}: {message}")
+            }
+        }
+    }
+}
+
+impl ConfigValue {
+    /// The key of this value in the configuration file
+    pub fn key(&self) -> &'static str {
+        match self {
+            ConfigValue::Value(_) => "value",
+            ConfigValue::ValueFromEnv(_) => "valueFromEnv",
+            ConfigValue::ValueFromEnvOrDefault { .. } => "valueFromEnvOrDefault",
+            ConfigValue::ValueFromFile(_) => "valueFromFile",
+            ConfigValue::ValueFromTemplate(_) => "valueFromTemplate",
+        }
+    }
+    /// Resolve the value, reading environment variables from `environment`
+    /// and files relative to `configuration_dir`
+    pub fn resolve(
+        &self,
+        environment: &HashMap<String, String>,
+        configuration_dir: &Path,
+    ) -> Result<String, ConfigValueError> {
+        match self {
+            ConfigValue::Value(value) => Ok(value.to_owned()),
+            ConfigValue::ValueFromEnv(name) => environment
+                .get(name)
+                .cloned()
+                .ok_or_else(|| ConfigValueError::EnvironmentVariableNotSet(name.to_owned())),
+            ConfigValue::ValueFromEnvOrDefault { variable, default } => Ok(environment
+                .get(variable)
+                .cloned()
+                .unwrap_or_else(|| default.to_owned())),
+            ConfigValue::ValueFromFile(path) => {
+                let path = configuration_dir.join(path);
+                let value =
+                    std::fs::read_to_string(&path).map_err(|err| ConfigValueError::ReadFile {
+                        path: path.clone(),
+                        message: err.to_string(),
+                    })?;
+                // mounted secrets commonly end with a line break that is not part of the value
+                Ok(value.trim_end_matches(['\n', '\r']).to_owned())
+            }
+            ConfigValue::ValueFromTemplate(template) => {
+                let mut value = String::new();
+                for part in template_parts(template)? {
+                    match part {
+                        TemplatePart::Text(text) => value.push_str(text),
+                        TemplatePart::EnvironmentVariable(name) => {
+                            value.push_str(environment.get(name).ok_or_else(|| {
+                                ConfigValueError::EnvironmentVariableNotSet(name.to_owned())
+                            })?);
+                        }
+                    }
+                }
+                Ok(value)
+            }
+        }
+    }
+    /// The environment variables this value reads, for values that read any
+    pub fn environment_variables(&self) -> Result<Vec<&str>, ConfigValueError> {
+        match self {
+            ConfigValue::ValueFromEnv(name) => Ok(vec![name]),
+            ConfigValue::ValueFromEnvOrDefault { variable, .. } => Ok(vec![variable]),
+            ConfigValue::ValueFromTemplate(template) => Ok(template_parts(template)?
+                .into_iter()
+                .filter_map(|part| match part {
+                    TemplatePart::Text(_) => None,
+                    TemplatePart::EnvironmentVariable(name) => Some(name),
+                })
+                .collect()),
+            ConfigValue::Value(_) | ConfigValue::ValueFromFile(_) => Ok(vec![]),
+        }
+    }
+}
+
+impl ConnectionConfigFile {
+    /// Resolve the endpoint and all headers, see [`ConfigValue::resolve`]
+    pub fn resolve(
+        &self,
+        environment: &HashMap<String, String>,
+        configuration_dir: &Path,
+    ) -> Result<ConnectionConfig, ConfigValueError> {
+        Ok(ConnectionConfig {
+            endpoint: self.endpoint.resolve(environment, configuration_dir)?,
+            headers: self
+                .headers
+                .iter()
+                .map(|(header_name, header_value)| {
+                    Ok((
+                        header_name.to_owned(),
+                        header_value.resolve(environment, configuration_dir)?,
+                    ))
+                })
+                .collect::<Result<_, ConfigValueError>>()?,
+            oauth2: self
+                .oauth2
+                .as_ref()
+                .map(|oauth2| oauth2.resolve(environment, configuration_dir))
+                .transpose()?,
+        })
+    }
+}
+
+impl OAuth2ConfigFile {
+    /// Resolve the token URL and client credentials, see [`ConfigValue::resolve`]
+    pub fn resolve(
+        &self,
+        environment: &HashMap<String, String>,
+        configuration_dir: &Path,
+    ) -> Result<OAuth2Config, ConfigValueError> {
+        Ok(OAuth2Config {
+            token_url: self.token_url.resolve(environment, configuration_dir)?,
+            client_id: self.client_id.resolve(environment, configuration_dir)?,
+            client_secret: self.client_secret.resolve(environment, configuration_dir)?,
+            scopes: self.scopes.clone().unwrap_or_default(),
+            audience: self.audience.clone(),
+        })
+    }
+    /// The values of the flow with their keys in the configuration file
+    pub fn values(&self) -> [(&'static str, &ConfigValue); 3] {
+        [
+            ("tokenUrl", &self.token_url),
+            ("clientId", &self.client_id),
+            ("clientSecret", &self.client_secret),
+        ]
+    }
+}
+
+enum TemplatePart<'a> {
+    Text(&'a str),
+    EnvironmentVariable(&'a str),
+}
+
+/// Split a template such as `Bearer {{env.TOKEN}}` into text and placeholders
+fn template_parts(template: &str) -> Result<Vec<TemplatePart<'_>>, ConfigValueError> {
+    let invalid_template = |message: String| ConfigValueError::InvalidTemplate {
+        template: template.to_owned(),
+        message,
+    };
+
+    let mut parts = vec![];
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            parts.push(TemplatePart::Text(&rest[..start]));
+        }
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| invalid_template("unclosed placeholder".to_owned()))?;
+        let placeholder = rest[start + 2..start + end].trim();
+        let name = placeholder
+            .strip_prefix("env.")
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| {
+                invalid_template(format!(
+                    "placeholder {{{{{placeholder}}}}} should have the form {{{{env.NAME}}}}"
+                ))
+            })?;
+        parts.push(TemplatePart::EnvironmentVariable(name));
+        rest = &rest[start + end + 2..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest));
+    }
+
+    Ok(parts)
+}
+
+#[test]
+fn test_resolve_config_value() {
+    let environment = HashMap::from([
+        ("TOKEN".to_owned(), "secret".to_owned()),
+        ("USER".to_owned(), "admin".to_owned()),
+    ]);
+    let configuration_dir = std::env::temp_dir().join(format!(
+        "ndc-graphql-config-value-test-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&configuration_dir).unwrap();
+    std::fs::write(configuration_dir.join("token"), "file-secret\n").unwrap();
+
+    let resolve = |value: ConfigValue| value.resolve(&environment, &configuration_dir);
+
+    assert_eq!(
+        resolve(ConfigValue::Value("a".to_owned())),
+        Ok("a".to_owned())
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromEnv("TOKEN".to_owned())),
+        Ok("secret".to_owned())
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromEnv("MISSING".to_owned())),
+        Err(ConfigValueError::EnvironmentVariableNotSet(
+            "MISSING".to_owned()
+        ))
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromEnvOrDefault {
+            variable: "MISSING".to_owned(),
+            default: "fallback".to_owned(),
+        }),
+        Ok("fallback".to_owned())
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromEnvOrDefault {
+            variable: "TOKEN".to_owned(),
+            default: "fallback".to_owned(),
+        }),
+        Ok("secret".to_owned())
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromFile("token".to_owned())),
+        Ok("file-secret".to_owned())
+    );
+    assert!(matches!(
+        resolve(ConfigValue::ValueFromFile("missing".to_owned())),
+        Err(ConfigValueError::ReadFile { .. })
+    ));
+    assert_eq!(
+        resolve(ConfigValue::ValueFromTemplate(
+            "Bearer {{env.TOKEN}} for {{ env.USER }}".to_owned()
+        )),
+        Ok("Bearer secret for admin".to_owned())
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromTemplate(
+            "Bearer {{env.MISSING}}".to_owned()
+        )),
+        Err(ConfigValueError::EnvironmentVariableNotSet(
+            "MISSING".to_owned()
+        ))
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromTemplate("Bearer {{TOKEN}}".to_owned()))
+            .map_err(|err| err.to_string()),
+        Err(
+            "Invalid template \"Bearer {{TOKEN}}\": placeholder {{TOKEN}} should have the form {{env.NAME}}"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        resolve(ConfigValue::ValueFromTemplate(
+            "Bearer {{env.TOKEN".to_owned()
+        ))
+        .map_err(|err| err.to_string()),
+        Err("Invalid template \"Bearer {{env.TOKEN\": unclosed placeholder".to_owned())
+    );
+
+    std::fs::remove_dir_all(&configuration_dir).unwrap();
+}
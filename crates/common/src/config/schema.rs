@@ -1,7 +1,93 @@
-use crate::config::{RequestConfig, ResponseConfig};
-use graphql_parser::schema;
+use crate::config::{
+    config_file::{ScalarRepresentation, SCHEMA_FILE_NAME},
+    NameMapping, SchemaConfig,
+};
+use filter::filter_definitions;
+use graphql_parser::{schema, Pos};
+use merge::{
+    mark_schema_extension, merge_schema_extension, split_schema_extensions,
+    type_definition_position, MergedDocument,
+};
 use ndc_models::{ArgumentName, FieldName, FunctionName, ProcedureName, ScalarTypeName, TypeName};
-use std::{collections::BTreeMap, fmt::Display};
+use references::check_type_references;
+use scalars::apply_scalar_representations;
+pub use scalars::{
+    default_scalar_representations, defined_scalar_names, infer_scalar_representation,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    iter,
+};
+
+mod filter;
+mod merge;
+mod references;
+mod scalars;
+
+/// A schema document that could not be parsed, with the position of the problem when it is known
+#[derive(Debug)]
+pub struct SchemaParseError {
+    pub position: Option<Pos>,
+    pub message: String,
+}
+
+impl std::error::Error for SchemaParseError {}
+
+impl Display for SchemaParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Parse a schema document. graphql-parser cannot parse `extend schema`, so extensions are parsed separately
+/// as schema definitions, and marked so they are merged into the schema definition.
+pub fn parse_schema(
+    schema_string: &str,
+) -> Result<schema::Document<'static, String>, SchemaParseError> {
+    let (document_string, extensions) = split_schema_extensions(schema_string);
+    let mut schema_document = parse_document(&document_string)?;
+
+    for (position, extension_string) in extensions {
+        for definition in parse_document(&extension_string)?.definitions {
+            if let schema::Definition::SchemaDefinition(mut extension) = definition {
+                extension.position = position;
+                mark_schema_extension(&mut extension);
+                schema_document
+                    .definitions
+                    .push(schema::Definition::SchemaDefinition(extension));
+            }
+        }
+    }
+
+    Ok(schema_document)
+}
+
+fn parse_document(
+    schema_string: &str,
+) -> Result<schema::Document<'static, String>, SchemaParseError> {
+    graphql_parser::parse_schema::<String>(schema_string)
+        .map(schema::Document::into_static)
+        .map_err(|err| {
+            let message = err.to_string();
+            SchemaParseError {
+                position: parse_error_position(&message),
+                message,
+            }
+        })
+}
+
+/// graphql-parser only exposes parse errors as strings, formatted as `schema parse error: Parse error at {line}:{column}`, followed by details.
+fn parse_error_position(message: &str) -> Option<Pos> {
+    let position = message.strip_prefix("schema parse error: Parse error at ")?;
+    let position = position.split_whitespace().next()?;
+    let (line, column) = position.split_once(':')?;
+
+    Some(Pos {
+        line: line.parse().ok()?,
+        column: column.parse().ok()?,
+    })
+}
 
 #[derive(Debug, Clone)]
 pub struct SchemaDefinition {
@@ -9,171 +95,500 @@ pub struct SchemaDefinition {
     pub query_fields: BTreeMap<FunctionName, ObjectFieldDefinition>,
     pub mutation_type_name: Option<TypeName>,
     pub mutation_fields: BTreeMap<ProcedureName, ObjectFieldDefinition>,
+    pub subscription_type_name: Option<TypeName>,
+    pub root_types_source: RootTypesSource,
     pub definitions: BTreeMap<TypeName, TypeDef>,
+    /// Query fields exposed as procedures instead of functions
+    pub query_procedures: BTreeSet<FunctionName>,
+    /// Mutation fields exposed as functions instead of procedures
+    pub mutation_functions: BTreeSet<ProcedureName>,
+    /// Interface and union types referenced by fields or arguments, which were removed as these types are not supported
+    pub unsupported_types: BTreeSet<TypeName>,
+    /// Root types referenced by fields, eg. `query: Query` in Relay mutation payloads, which were removed
+    pub referenced_root_types: BTreeSet<TypeName>,
+}
+
+/// The upstream operation type a root field belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationType {
+    Query,
+    Mutation,
+}
+
+/// How the root operation types of a schema were determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootTypesSource {
+    /// Root types were named in a `schema { ... }` definition
+    SchemaDefinition,
+    /// No schema definition was found, so the default names `Query`, `Mutation` and `Subscription` were used
+    DefaultNames,
 }
 
 impl SchemaDefinition {
     pub fn new(
         schema_document: &schema::Document<'_, String>,
-        request_config: &RequestConfig,
-        response_config: &ResponseConfig,
-    ) -> Result<Self, SchemaDefinitionError> {
-        let schema_definition = schema_document
-            .definitions
-            .iter()
-            .find_map(|def| match def {
-                schema::Definition::SchemaDefinition(schema) => Some(schema),
-                schema::Definition::TypeDefinition(_)
-                | schema::Definition::TypeExtension(_)
-                | schema::Definition::DirectiveDefinition(_) => None,
-            })
-            .ok_or(SchemaDefinitionError::MissingSchemaType)?;
+        config: &SchemaConfig,
+    ) -> Result<Self, SchemaDefinitionErrors> {
+        let SchemaConfig {
+            request: request_config,
+            response: response_config,
+            scalars,
+            filter,
+            naming,
+            operations,
+        } = config;
+        let mut errors = vec![];
 
-        // note: if there are duplicate definitions, the last one will stick.
-        let definitions: BTreeMap<TypeName, TypeDef> = schema_document
-            .definitions
-            .iter()
-            .filter_map(|definition| match definition {
-                schema::Definition::SchemaDefinition(_) => None,
-                schema::Definition::DirectiveDefinition(_) => None,
-                schema::Definition::TypeExtension(_) => None,
-                schema::Definition::TypeDefinition(type_definition) => match type_definition {
-                    schema::TypeDefinition::Union(_) => None,
-                    schema::TypeDefinition::Interface(_) => None,
-                    schema::TypeDefinition::Scalar(scalar) => Some(TypeDef::new_scalar(scalar)),
-                    schema::TypeDefinition::Object(object) => {
-                        // skip query, mutation, subscription types
-                        if schema_definition
-                            .query
-                            .as_ref()
-                            .is_some_and(|query_type| query_type == &object.name)
-                            || schema_definition
-                                .subscription
-                                .as_ref()
-                                .is_some_and(|subscription_type| subscription_type == &object.name)
-                            || schema_definition
-                                .mutation
-                                .as_ref()
-                                .is_some_and(|mutation_type| mutation_type == &object.name)
-                        {
-                            None
-                        } else {
-                            Some(TypeDef::new_object(object))
-                        }
-                    }
-                    schema::TypeDefinition::Enum(enum_definition) => {
-                        Some(TypeDef::new_enum(enum_definition))
-                    }
-                    schema::TypeDefinition::InputObject(input_object) => {
-                        Some(TypeDef::new_input_object(input_object))
-                    }
-                },
-            })
-            .collect();
+        let MergedDocument {
+            schema_definition,
+            schema_extensions,
+            type_definitions,
+        } = MergedDocument::new(schema_document, &mut errors);
 
-        if definitions.contains_key(request_config.headers_type_name.inner()) {
-            return Err(SchemaDefinitionError::HeaderTypeNameConflict(
-                request_config.headers_type_name.to_owned(),
-            ));
+        let (mut schema_definition, root_types_source) = match schema_definition {
+            Some(schema_definition) => (schema_definition, RootTypesSource::SchemaDefinition),
+            None => (
+                default_schema_definition(&type_definitions),
+                RootTypesSource::DefaultNames,
+            ),
+        };
+
+        for extension in &schema_extensions {
+            merge_schema_extension(&mut schema_definition, extension, &mut errors);
         }
 
-        let query_type = schema_document
-            .definitions
-            .iter()
-            .find_map(|def| match def {
-                schema::Definition::TypeDefinition(schema::TypeDefinition::Object(query_type))
+        if schema_definition.query.is_none()
+            && schema_definition.mutation.is_none()
+            && schema_definition.subscription.is_none()
+        {
+            errors.push(LocatedSchemaDefinitionError {
+                position: None,
+                error: SchemaDefinitionError::MissingSchemaType,
+            });
+            return Err(SchemaDefinitionErrors(errors));
+        }
+
+        let mut definitions: BTreeMap<TypeName, TypeDef> = type_definitions
+            .values()
+            .filter_map(|type_definition| match type_definition {
+                schema::TypeDefinition::Union(_) => None,
+                schema::TypeDefinition::Interface(_) => None,
+                schema::TypeDefinition::Scalar(scalar) => Some(TypeDef::new_scalar(scalar)),
+                schema::TypeDefinition::Object(object) => {
+                    // skip query, mutation, subscription types
                     if schema_definition
                         .query
                         .as_ref()
-                        .is_some_and(|query_type_name| query_type_name == &query_type.name) =>
-                {
-                    Some(query_type)
+                        .is_some_and(|query_type| query_type == &object.name)
+                        || schema_definition
+                            .subscription
+                            .as_ref()
+                            .is_some_and(|subscription_type| subscription_type == &object.name)
+                        || schema_definition
+                            .mutation
+                            .as_ref()
+                            .is_some_and(|mutation_type| mutation_type == &object.name)
+                    {
+                        None
+                    } else {
+                        Some(TypeDef::new_object(object))
+                    }
                 }
-                _ => None,
-            });
+                schema::TypeDefinition::Enum(enum_definition) => {
+                    Some(TypeDef::new_enum(enum_definition))
+                }
+                schema::TypeDefinition::InputObject(input_object) => {
+                    Some(TypeDef::new_input_object(input_object))
+                }
+            })
+            .collect();
+
+        // conflicts are between NDC names, so look up which type, if any, would be renamed to a generated name
+        if let Some(type_definition) = naming
+            .types
+            .upstream_name(request_config.headers_type_name.as_str())
+            .and_then(|upstream_name| type_definitions.get(&upstream_name))
+        {
+            errors.push(
+                SchemaDefinitionError::HeaderTypeNameConflict(
+                    request_config.headers_type_name.to_owned(),
+                )
+                .at(type_definition_position(type_definition)),
+            );
+        }
+
+        let query_type = root_type(
+            "query",
+            schema_definition.query.as_ref(),
+            &schema_definition,
+            &type_definitions,
+            &mut errors,
+        );
         let mut query_fields = BTreeMap::new();
+        let mut root_fields = vec![];
 
         if let Some(query_type) = query_type {
             for field in &query_type.fields {
-                let query_field = field.name.to_owned().into();
-                let response_type = response_config.query_response_type_name(&query_field);
-
-                if definitions.contains_key(&response_type) {
-                    return Err(SchemaDefinitionError::QueryResponseTypeConflict {
-                        query_field,
-                        response_type,
-                    });
+                if !filter.queries.includes(&field.name) {
+                    continue;
                 }
+                root_fields.push((&query_type.name, field));
+                let query_field: FunctionName = field.name.to_owned().into();
+                let response_type = if operations.query_field_is_procedure(&field.name) {
+                    response_config.mutation_response_type_name(
+                        &naming.procedures.ndc_name(&field.name).into(),
+                    )
+                } else {
+                    response_config
+                        .query_response_type_name(&naming.functions.ndc_name(&field.name).into())
+                };
 
                 let field_definition = ObjectFieldDefinition::new(field);
 
+                if naming
+                    .types
+                    .upstream_name(response_type.as_str())
+                    .is_some_and(|upstream_name| definitions.contains_key(upstream_name.as_str()))
+                {
+                    errors.push(
+                        SchemaDefinitionError::QueryResponseTypeConflict {
+                            query_field: query_field.clone(),
+                            response_type,
+                        }
+                        .at(field.position),
+                    );
+                }
+
                 if field_definition
                     .arguments
                     .contains_key(&request_config.headers_argument)
                 {
-                    return Err(SchemaDefinitionError::QueryHeaderArgumentConflict {
-                        query_field,
-                        headers_argument: request_config.headers_argument.to_owned(),
-                    });
+                    errors.push(
+                        SchemaDefinitionError::QueryHeaderArgumentConflict {
+                            query_field,
+                            headers_argument: request_config.headers_argument.to_owned(),
+                        }
+                        .at(field.position),
+                    );
                 }
 
                 query_fields.insert(field.name.to_owned().into(), field_definition);
             }
         }
 
-        let mutation_type =
-            schema_document
-                .definitions
-                .iter()
-                .find_map(|def| match def {
-                    schema::Definition::TypeDefinition(schema::TypeDefinition::Object(
-                        mutation_type,
-                    )) if schema_definition.mutation.as_ref().is_some_and(
-                        |mutation_type_name| mutation_type_name == &mutation_type.name,
-                    ) =>
-                    {
-                        Some(mutation_type)
-                    }
-                    _ => None,
-                });
+        let mutation_type = root_type(
+            "mutation",
+            schema_definition.mutation.as_ref(),
+            &schema_definition,
+            &type_definitions,
+            &mut errors,
+        );
         let mut mutation_fields = BTreeMap::new();
 
         if let Some(mutation_type) = mutation_type {
             for field in &mutation_type.fields {
-                let mutation_field = field.name.to_owned().into();
-                let response_type = response_config.mutation_response_type_name(&mutation_field);
-
-                if definitions.contains_key(&response_type) {
-                    return Err(SchemaDefinitionError::MutationResponseTypeConflict {
-                        mutation_field,
-                        response_type,
-                    });
+                if !filter.mutations.includes(&field.name) {
+                    continue;
                 }
+                root_fields.push((&mutation_type.name, field));
+                let mutation_field: ProcedureName = field.name.to_owned().into();
+                let response_type = if operations.mutation_field_is_function(&field.name) {
+                    response_config
+                        .query_response_type_name(&naming.functions.ndc_name(&field.name).into())
+                } else {
+                    response_config.mutation_response_type_name(
+                        &naming.procedures.ndc_name(&field.name).into(),
+                    )
+                };
 
                 let field_definition = ObjectFieldDefinition::new(field);
 
+                if naming
+                    .types
+                    .upstream_name(response_type.as_str())
+                    .is_some_and(|upstream_name| definitions.contains_key(upstream_name.as_str()))
+                {
+                    errors.push(
+                        SchemaDefinitionError::MutationResponseTypeConflict {
+                            mutation_field: mutation_field.clone(),
+                            response_type,
+                        }
+                        .at(field.position),
+                    );
+                }
+
                 if field_definition
                     .arguments
                     .contains_key(&request_config.headers_argument)
                 {
-                    return Err(SchemaDefinitionError::MutationHeaderArgumentConflict {
-                        mutation_field,
-                        headers_argument: request_config.headers_argument.to_owned(),
-                    });
+                    errors.push(
+                        SchemaDefinitionError::MutationHeaderArgumentConflict {
+                            mutation_field,
+                            headers_argument: request_config.headers_argument.to_owned(),
+                        }
+                        .at(field.position),
+                    );
                 }
 
                 mutation_fields.insert(field.name.to_owned().into(), field_definition);
             }
         }
 
+        let (unsupported_types, referenced_root_types) = check_type_references(
+            &type_definitions,
+            &root_fields,
+            &mut definitions,
+            &mut errors,
+        );
+
+        apply_scalar_representations(scalars, &mut definitions, &mut errors);
+
+        // types referenced by root fields before filtering, so we can tell which types filtering made unreachable
+        let root_type_references: BTreeSet<TypeName> = query_type
+            .iter()
+            .chain(mutation_type.iter())
+            .flat_map(|root_type| &root_type.fields)
+            .flat_map(|field| {
+                iter::once(&field.field_type)
+                    .chain(field.arguments.iter().map(|argument| &argument.value_type))
+            })
+            .map(|type_reference| TypeRef::new(type_reference).name())
+            .collect();
+
+        filter_definitions(
+            filter,
+            &root_type_references,
+            &unsupported_types
+                .union(&referenced_root_types)
+                .cloned()
+                .collect(),
+            &mut query_fields,
+            &mut mutation_fields,
+            &mut definitions,
+        );
+
+        let query_procedures: BTreeSet<FunctionName> = query_fields
+            .keys()
+            .filter(|name| operations.query_field_is_procedure(name.as_str()))
+            .cloned()
+            .collect();
+        let mutation_functions: BTreeSet<ProcedureName> = mutation_fields
+            .keys()
+            .filter(|name| operations.mutation_field_is_function(name.as_str()))
+            .cloned()
+            .collect();
+
+        check_naming_conflicts(
+            "functions",
+            &naming.functions,
+            query_fields
+                .keys()
+                .filter(|name| !query_procedures.contains(*name))
+                .map(FunctionName::as_str)
+                .chain(mutation_functions.iter().map(ProcedureName::as_str)),
+            &mut errors,
+        );
+        check_naming_conflicts(
+            "procedures",
+            &naming.procedures,
+            mutation_fields
+                .keys()
+                .filter(|name| !mutation_functions.contains(*name))
+                .map(ProcedureName::as_str)
+                .chain(query_procedures.iter().map(FunctionName::as_str)),
+            &mut errors,
+        );
+        check_naming_conflicts(
+            "types",
+            &naming.types,
+            definitions.keys().map(TypeName::as_str),
+            &mut errors,
+        );
+
+        if !errors.is_empty() {
+            return Err(SchemaDefinitionErrors(errors));
+        }
+
         Ok(Self {
             query_fields,
             query_type_name: schema_definition.query.to_owned().map(Into::into),
             mutation_fields,
             mutation_type_name: schema_definition.mutation.to_owned().map(Into::into),
+            subscription_type_name: schema_definition.subscription.to_owned().map(Into::into),
+            root_types_source,
             definitions,
+            query_procedures,
+            mutation_functions,
+            unsupported_types,
+            referenced_root_types,
         })
     }
+    /// Root fields exposed as functions, by field name
+    pub fn function_fields(
+        &self,
+    ) -> impl Iterator<Item = (&str, OperationType, &ObjectFieldDefinition)> {
+        let query_fields = self
+            .query_fields
+            .iter()
+            .filter(|(name, _)| !self.query_procedures.contains(*name))
+            .map(|(name, field)| (name.as_str(), OperationType::Query, field));
+        let mutation_fields = self
+            .mutation_fields
+            .iter()
+            .filter(|(name, _)| self.mutation_functions.contains(*name))
+            .map(|(name, field)| (name.as_str(), OperationType::Mutation, field));
+        query_fields.chain(mutation_fields)
+    }
+    /// Root fields exposed as procedures, by field name
+    pub fn procedure_fields(
+        &self,
+    ) -> impl Iterator<Item = (&str, OperationType, &ObjectFieldDefinition)> {
+        let mutation_fields = self
+            .mutation_fields
+            .iter()
+            .filter(|(name, _)| !self.mutation_functions.contains(*name))
+            .map(|(name, field)| (name.as_str(), OperationType::Mutation, field));
+        let query_fields = self
+            .query_fields
+            .iter()
+            .filter(|(name, _)| self.query_procedures.contains(*name))
+            .map(|(name, field)| (name.as_str(), OperationType::Query, field));
+        mutation_fields.chain(query_fields)
+    }
+    /// Look up the root field exposed as a function with the given field name
+    pub fn function_field(
+        &self,
+        field_name: &str,
+    ) -> Option<(OperationType, &ObjectFieldDefinition)> {
+        self.function_fields()
+            .find(|(name, _, _)| *name == field_name)
+            .map(|(_, operation_type, field)| (operation_type, field))
+    }
+    /// Look up the root field exposed as a procedure with the given field name
+    pub fn procedure_field(
+        &self,
+        field_name: &str,
+    ) -> Option<(OperationType, &ObjectFieldDefinition)> {
+        self.procedure_fields()
+            .find(|(name, _, _)| *name == field_name)
+            .map(|(_, operation_type, field)| (operation_type, field))
+    }
+    /// Describe which root operation types were detected, and how
+    pub fn describe_root_types(&self) -> String {
+        let source = match self.root_types_source {
+            RootTypesSource::SchemaDefinition => "from schema definition",
+            RootTypesSource::DefaultNames => "no schema definition found, using default names",
+        };
+        let type_name = |name: &Option<TypeName>| {
+            name.as_ref()
+                .map_or_else(|| "none".to_string(), ToString::to_string)
+        };
+
+        format!(
+            "Root operation types ({source}): query: {}, mutation: {}, subscription: {}",
+            type_name(&self.query_type_name),
+            type_name(&self.mutation_type_name),
+            type_name(&self.subscription_type_name),
+        )
+    }
+    /// Describe the fields and arguments removed because they reference interface, union or root types, if any were
+    pub fn describe_unsupported_types(&self) -> Option<String> {
+        let describe = |description: &str, types: &BTreeSet<TypeName>| {
+            (!types.is_empty()).then(|| {
+                let type_names: Vec<&str> = types.iter().map(TypeName::as_str).collect();
+                format!("{description}: {}", type_names.join(", "))
+            })
+        };
+
+        let descriptions: Vec<String> = [
+            describe(
+                "Interface and union types are not supported, fields and arguments referencing these types were removed",
+                &self.unsupported_types,
+            ),
+            describe(
+                "Root types cannot be used as field types, fields referencing these types were removed",
+                &self.referenced_root_types,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        (!descriptions.is_empty()).then(|| descriptions.join("\n"))
+    }
+}
+
+/// Renaming must not give multiple functions, procedures, or types the same NDC name
+fn check_naming_conflicts<'a>(
+    kind: &'static str,
+    mapping: &NameMapping,
+    upstream_names: impl Iterator<Item = &'a str>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) {
+    let mut ndc_names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for upstream_name in upstream_names {
+        ndc_names
+            .entry(mapping.ndc_name(upstream_name))
+            .or_default()
+            .push(upstream_name.to_owned());
+    }
+
+    for (name, upstream_names) in ndc_names {
+        if upstream_names.len() > 1 {
+            errors.push(LocatedSchemaDefinitionError {
+                position: None,
+                error: SchemaDefinitionError::NamingConflict {
+                    kind,
+                    name,
+                    upstream_names,
+                },
+            });
+        }
+    }
+}
+
+/// Look up a root operation type, which must be an object type if named in the schema definition.
+fn root_type<'a, 'b>(
+    operation: &'static str,
+    type_name: Option<&String>,
+    schema_definition: &schema::SchemaDefinition<'a, String>,
+    type_definitions: &'b BTreeMap<String, schema::TypeDefinition<'a, String>>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) -> Option<&'b schema::ObjectType<'a, String>> {
+    let type_name = type_name?;
+
+    match type_definitions.get(type_name) {
+        Some(schema::TypeDefinition::Object(object)) => Some(object),
+        _ => {
+            errors.push(
+                SchemaDefinitionError::MissingRootType {
+                    operation,
+                    type_name: type_name.to_owned().into(),
+                }
+                .at(schema_definition.position),
+            );
+            None
+        }
+    }
+}
+
+/// Per the GraphQL spec, when a schema has no schema definition, root operation types
+/// are the object types named `Query`, `Mutation` and `Subscription`, if they exist.
+fn default_schema_definition<'a>(
+    type_definitions: &BTreeMap<String, schema::TypeDefinition<'a, String>>,
+) -> schema::SchemaDefinition<'a, String> {
+    let root_type = |name: &str| match type_definitions.get(name) {
+        Some(schema::TypeDefinition::Object(_)) => Some(name.to_string()),
+        _ => None,
+    };
+
+    schema::SchemaDefinition {
+        query: root_type("Query"),
+        mutation: root_type("Mutation"),
+        subscription: root_type("Subscription"),
+        ..Default::default()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -199,9 +614,20 @@ impl TypeRef {
     }
 }
 
+impl Display for TypeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeRef::Named(name) => write!(f, "{name}"),
+            TypeRef::List(underlying) => write!(f, "[{underlying}]"),
+            TypeRef::NonNull(underlying) => write!(f, "{underlying}!"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TypeDef {
     Scalar {
+        representation: ScalarRepresentation,
         description: Option<String>,
     },
     Enum {
@@ -215,6 +641,8 @@ pub enum TypeDef {
     InputObject {
         fields: BTreeMap<FieldName, InputObjectFieldDefinition>,
         description: Option<String>,
+        /// set for input objects marked `@oneOf`, which require exactly one field to be set to a non-null value
+        one_of: bool,
     },
 }
 
@@ -223,6 +651,7 @@ impl TypeDef {
         (
             scalar_definition.name.to_owned().into(),
             Self::Scalar {
+                representation: scalars::default_scalar_representation(&scalar_definition.name),
                 description: scalar_definition.description.to_owned(),
             },
         )
@@ -275,6 +704,10 @@ impl TypeDef {
                     })
                     .collect(),
                 description: input_object_definition.description.to_owned(),
+                one_of: input_object_definition
+                    .directives
+                    .iter()
+                    .any(|directive| directive.name == "oneOf"),
             },
         )
     }
@@ -284,6 +717,8 @@ impl TypeDef {
 pub struct EnumValueDefinition {
     pub name: String,
     pub description: Option<String>,
+    /// set if the value is marked `@deprecated`
+    pub deprecation_reason: Option<String>,
 }
 
 impl EnumValueDefinition {
@@ -291,6 +726,7 @@ impl EnumValueDefinition {
         Self {
             name: value.name.to_owned(),
             description: value.description.to_owned(),
+            deprecation_reason: deprecation_reason(&value.directives),
         }
     }
 }
@@ -300,6 +736,8 @@ pub struct ObjectFieldDefinition {
     pub r#type: TypeRef,
     pub arguments: BTreeMap<ArgumentName, ObjectFieldArgumentDefinition>,
     pub description: Option<String>,
+    /// set if the field is marked `@deprecated`
+    pub deprecation_reason: Option<String>,
 }
 
 impl ObjectFieldDefinition {
@@ -317,6 +755,7 @@ impl ObjectFieldDefinition {
                 })
                 .collect(),
             description: field.description.to_owned(),
+            deprecation_reason: deprecation_reason(&field.directives),
         }
     }
 }
@@ -325,13 +764,26 @@ impl ObjectFieldDefinition {
 pub struct ObjectFieldArgumentDefinition {
     pub r#type: TypeRef,
     pub description: Option<String>,
+    /// arguments with a default value may be omitted, even if non-null
+    pub default_value: Option<schema::Value<'static, String>>,
+    /// set if the argument is marked `@deprecated`
+    pub deprecation_reason: Option<String>,
 }
 
 impl ObjectFieldArgumentDefinition {
+    /// non-null arguments without a default value must be provided
+    pub fn is_required(&self) -> bool {
+        matches!(self.r#type, TypeRef::NonNull(_)) && self.default_value.is_none()
+    }
     fn new(argument: &schema::InputValue<String>) -> Self {
         Self {
             r#type: TypeRef::new(&argument.value_type),
             description: argument.description.to_owned(),
+            default_value: argument
+                .default_value
+                .as_ref()
+                .map(|value| value.clone().into_static()),
+            deprecation_reason: deprecation_reason(&argument.directives),
         }
     }
 }
@@ -340,13 +792,76 @@ impl ObjectFieldArgumentDefinition {
 pub struct InputObjectFieldDefinition {
     pub r#type: TypeRef,
     pub description: Option<String>,
+    /// fields with a default value may be omitted, even if non-null
+    pub default_value: Option<schema::Value<'static, String>>,
+    /// set if the field is marked `@deprecated`
+    pub deprecation_reason: Option<String>,
 }
 
 impl InputObjectFieldDefinition {
+    /// non-null fields without a default value must be provided
+    pub fn is_required(&self) -> bool {
+        matches!(self.r#type, TypeRef::NonNull(_)) && self.default_value.is_none()
+    }
     fn new(field: &schema::InputValue<String>) -> Self {
         Self {
             r#type: TypeRef::new(&field.value_type),
             description: field.description.to_owned(),
+            default_value: field
+                .default_value
+                .as_ref()
+                .map(|value| value.clone().into_static()),
+            deprecation_reason: deprecation_reason(&field.directives),
+        }
+    }
+}
+
+/// The reason given by a `@deprecated` directive, defaulting to the reason specified by GraphQL
+fn deprecation_reason(directives: &[schema::Directive<String>]) -> Option<String> {
+    let directive = directives
+        .iter()
+        .find(|directive| directive.name == "deprecated")?;
+    let reason =
+        directive
+            .arguments
+            .iter()
+            .find_map(|(name, value)| match (name.as_str(), value) {
+                ("reason", schema::Value::String(reason)) => Some(reason.to_owned()),
+                _ => None,
+            });
+    Some(reason.unwrap_or_else(|| "No longer supported".to_owned()))
+}
+
+/// All problems found while validating a schema document
+#[derive(Debug, Clone)]
+pub struct SchemaDefinitionErrors(pub Vec<LocatedSchemaDefinitionError>);
+
+impl std::error::Error for SchemaDefinitionErrors {}
+
+impl Display for SchemaDefinitionErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, error) in self.0.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A schema definition error, with the position in the schema document it originated from, if known
+#[derive(Debug, Clone)]
+pub struct LocatedSchemaDefinitionError {
+    pub position: Option<Pos>,
+    pub error: SchemaDefinitionError,
+}
+
+impl Display for LocatedSchemaDefinitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.position {
+            Some(position) => write!(f, "{SCHEMA_FILE_NAME}:{position}: {}", self.error),
+            None => write!(f, "{SCHEMA_FILE_NAME}: {}", self.error),
         }
     }
 }
@@ -354,6 +869,29 @@ impl InputObjectFieldDefinition {
 #[derive(Debug, Clone)]
 pub enum SchemaDefinitionError {
     MissingSchemaType,
+    MissingRootType {
+        operation: &'static str,
+        type_name: TypeName,
+    },
+    UnknownTypeReference {
+        type_name: TypeName,
+        referenced_by: String,
+    },
+    ScalarRepresentationTypeNotFound(ScalarTypeName),
+    EmptyScalarEnumRepresentation(ScalarTypeName),
+    DuplicateSchemaDefinition,
+    DuplicateTypeDefinition(TypeName),
+    DuplicateOperationType(String),
+    DuplicateTypeMember {
+        type_name: TypeName,
+        member: String,
+    },
+    TypeExtensionNotFound(TypeName),
+    TypeExtensionKindMismatch {
+        type_name: TypeName,
+        definition_kind: &'static str,
+        extension_kind: &'static str,
+    },
     HeaderTypeNameConflict(ScalarTypeName),
     QueryHeaderArgumentConflict {
         query_field: FunctionName,
@@ -371,17 +909,74 @@ pub enum SchemaDefinitionError {
         mutation_field: ProcedureName,
         response_type: TypeName,
     },
+    NamingConflict {
+        kind: &'static str,
+        name: String,
+        upstream_names: Vec<String>,
+    },
 }
 
 impl std::error::Error for SchemaDefinitionError {}
 
+impl SchemaDefinitionError {
+    fn at(self, position: Pos) -> LocatedSchemaDefinitionError {
+        LocatedSchemaDefinitionError {
+            position: Some(position),
+            error: self,
+        }
+    }
+    /// The path of the configuration file property this error can be resolved with, if any
+    pub fn config_node_path(&self) -> Option<Vec<String>> {
+        let path: &[&str] = match self {
+            SchemaDefinitionError::HeaderTypeNameConflict(_) => &["request", "headersTypeName"],
+            SchemaDefinitionError::QueryHeaderArgumentConflict { .. }
+            | SchemaDefinitionError::MutationHeaderArgumentConflict { .. } => {
+                &["request", "headersArgument"]
+            }
+            SchemaDefinitionError::QueryResponseTypeConflict { .. }
+            | SchemaDefinitionError::MutationResponseTypeConflict { .. } => {
+                &["response", "typeNamePrefix"]
+            }
+            SchemaDefinitionError::ScalarRepresentationTypeNotFound(name)
+            | SchemaDefinitionError::EmptyScalarEnumRepresentation(name) => {
+                return Some(vec!["scalars".to_owned(), name.to_string()]);
+            }
+            SchemaDefinitionError::NamingConflict { kind, .. } => {
+                return Some(vec!["naming".to_owned(), (*kind).to_owned()]);
+            }
+            _ => return None,
+        };
+        Some(path.iter().map(ToString::to_string).collect())
+    }
+}
+
 impl Display for SchemaDefinitionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SchemaDefinitionError::MissingSchemaType => write!(
                 f,
-                "Missing Schema Type: expected schema file with schema definition"
+                "Missing Schema Type: expected schema file with schema definition, or with a Query, Mutation or Subscription type"
             ),
+            SchemaDefinitionError::MissingRootType {
+                operation,
+                type_name,
+            } => write!(f, "Missing root type: The {operation} root type {type_name} is not defined as an object type"),
+            SchemaDefinitionError::UnknownTypeReference {
+                type_name,
+                referenced_by,
+            } => write!(f, "Unknown type reference: {referenced_by} references type {type_name}, which is not defined"),
+            SchemaDefinitionError::ScalarRepresentationTypeNotFound(name) => write!(f, "Scalar representation for unknown type: {name} is not a scalar type in the schema. Remove it from scalars"),
+            SchemaDefinitionError::EmptyScalarEnumRepresentation(name) => write!(f, "Empty enum representation: Scalar {name} has an enum representation with no values"),
+            SchemaDefinitionError::DuplicateSchemaDefinition => write!(f, "Duplicate schema definition: The schema is defined more than once. Use extend schema {{...}} to add to the schema definition"),
+            SchemaDefinitionError::DuplicateTypeDefinition(name) => write!(f, "Duplicate type definition: Type {name} is defined more than once. Use extend {{...}} to add to an existing type"),
+            SchemaDefinitionError::DuplicateOperationType(operation) => write!(f, "Duplicate operation type: The {operation} operation type is defined more than once in the schema definition"),
+            SchemaDefinitionError::DuplicateTypeMember { type_name, member } => write!(f, "Duplicate member definition: Type {type_name} defines {member} more than once"),
+            SchemaDefinitionError::TypeExtensionNotFound(name) => write!(f, "Type extension for unknown type: Type {name} is extended but never defined"),
+            SchemaDefinitionError::TypeExtensionKindMismatch {
+                type_name,
+                definition_kind,
+                extension_kind,
+            } => write!(f, "Type extension kind mismatch: Type {type_name} is defined as {definition_kind} but extended as {extension_kind}"),
             SchemaDefinitionError::HeaderTypeNameConflict(name) => write!(f, "HeaderMap name conflict: Another type with name {name} exists. Change the name under request.headerTypeName"),
             SchemaDefinitionError::QueryHeaderArgumentConflict {
                 query_field,
@@ -399,6 +994,188 @@ impl Display for SchemaDefinitionError {
                 mutation_field,
                 response_type,
             } => write!(f, "ResponseType name conflict for Mutation field {mutation_field}: A type with name {response_type} already exist. Change the response typename prefix or suffix under  response.typeNamePrefix or response.typeNameSuffix"),
+            SchemaDefinitionError::NamingConflict {
+                kind,
+                name,
+                upstream_names,
+            } => write!(f, "Naming conflict: {} would all be named {name}. Change the naming under naming.{kind}", upstream_names.join(", ")),
         }
     }
 }
+
+#[test]
+fn test_default_root_types() {
+    let schema_document = parse_schema(
+        r#"
+        type Query { a: String }
+        type Subscription { b: String }
+        "#,
+    )
+    .expect("schema should parse");
+
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect("schema should be valid");
+
+    assert_eq!(schema.root_types_source, RootTypesSource::DefaultNames);
+    assert_eq!(schema.query_type_name, Some("Query".to_string().into()));
+    assert_eq!(schema.mutation_type_name, None);
+    assert_eq!(
+        schema.subscription_type_name,
+        Some("Subscription".to_string().into())
+    );
+    assert!(schema.query_fields.contains_key("a"));
+    assert!(!schema.definitions.contains_key("Subscription"));
+
+    let schema_document = parse_schema("type Foo { a: String }").expect("schema should parse");
+
+    let errors = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect_err("schema without root types should be invalid");
+
+    assert!(matches!(
+        errors.0.as_slice(),
+        [LocatedSchemaDefinitionError {
+            position: None,
+            error: SchemaDefinitionError::MissingSchemaType
+        }]
+    ));
+}
+
+#[test]
+fn test_collect_all_errors() {
+    let schema_document = parse_schema(
+        r#"schema { query: Query, mutation: Mutation }
+type Query {
+  a(_headers: String): String
+  b(_headers: String): String
+}
+type _HeaderMap { c: String }"#,
+    )
+    .expect("schema should parse");
+
+    let errors = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect_err("schema should be invalid");
+
+    let errors: Vec<String> = errors.0.iter().map(ToString::to_string).collect();
+
+    assert_eq!(errors.len(), 4, "{errors:#?}");
+    assert!(errors[0].starts_with("schema.graphql:6:1: HeaderMap name conflict"));
+    assert!(errors[1].starts_with("schema.graphql:3:3: Query Headers argument conflict"));
+    assert!(errors[2].starts_with("schema.graphql:4:3: Query Headers argument conflict"));
+    assert!(errors[3].starts_with("schema.graphql:1:1: Missing root type"));
+}
+
+#[test]
+fn test_naming_conflicts() {
+    use crate::config::NamingConfig;
+
+    let schema_document = parse_schema(
+        r"
+type Query { user: User, users: [User] }
+type User { name: String }
+type Person { name: String }
+type _userQueryResponse { a: String }",
+    )
+    .expect("schema should parse");
+
+    let naming = NamingConfig {
+        functions: NameMapping {
+            rename: BTreeMap::from([("users".to_string(), "user".to_string())]),
+            ..NameMapping::default()
+        },
+        types: NameMapping {
+            rename: BTreeMap::from([("Person".to_string(), "User".to_string())]),
+            ..NameMapping::default()
+        },
+        ..NamingConfig::default()
+    };
+
+    let errors = SchemaDefinition::new(
+        &schema_document,
+        &SchemaConfig {
+            naming,
+            ..SchemaConfig::default()
+        },
+    )
+    .expect_err("schema should be invalid");
+
+    let errors: Vec<String> = errors.0.iter().map(ToString::to_string).collect();
+
+    assert_eq!(errors.len(), 4, "{errors:#?}");
+    assert!(errors[0].starts_with("schema.graphql:2:14: ResponseType name conflict"));
+    assert!(errors[1].starts_with("schema.graphql:2:26: ResponseType name conflict"));
+    assert_eq!(errors[2], "schema.graphql: Naming conflict: user, users would all be named user. Change the naming under naming.functions");
+    assert_eq!(errors[3], "schema.graphql: Naming conflict: Person, User would all be named User. Change the naming under naming.types");
+}
+
+#[test]
+fn test_parse_error_position() {
+    // pins the graphql-parser error format the position is read from
+    let message =
+        graphql_parser::parse_schema::<String>("type Query {\n  a: String\n  b(: String\n}")
+            .expect_err("schema should not parse")
+            .to_string();
+    assert!(
+        message.starts_with("schema parse error: Parse error at 3:5\n"),
+        "unexpected graphql-parser error format: {message}"
+    );
+
+    let error = parse_schema("type Query {\n  a: String\n  b(: String\n}")
+        .expect_err("schema should not parse");
+    assert_eq!(error.position, Some(Pos { line: 3, column: 5 }));
+    assert_eq!(error.message, message);
+}
+
+#[test]
+fn test_schema_extensions() {
+    let schema_document = parse_schema(
+        "schema { query: Query }\ntype Query { a: Int }\ntype Mutation { b: Int }\nextend schema @dir { mutation: Mutation }",
+    )
+    .expect("extend schema should parse");
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect("extend schema should merge");
+
+    assert_eq!(schema.root_types_source, RootTypesSource::SchemaDefinition);
+    assert_eq!(schema.mutation_type_name, Some("Mutation".into()));
+    assert!(schema.mutation_fields.contains_key("b"));
+
+    // extensions apply to the default schema definition too
+    let schema_document = parse_schema(
+        "type Query { a: Int }\ntype Root { b: Int }\nextend schema { mutation: Root }",
+    )
+    .expect("extend schema should parse");
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect("extend schema should merge");
+
+    assert_eq!(schema.root_types_source, RootTypesSource::DefaultNames);
+    assert_eq!(schema.query_type_name, Some("Query".into()));
+    assert_eq!(schema.mutation_type_name, Some("Root".into()));
+
+    let schema_document = parse_schema(
+        "schema { query: Query }\ntype Query { a: Int }\n  extend schema { query: Query }\nschema { query: Query }",
+    )
+    .expect("schema should parse");
+    let errors = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect_err("duplicate schema definitions should fail")
+        .0;
+
+    assert_eq!(
+        errors
+            .iter()
+            .map(|error| (error.position, error.error.to_string()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(Pos { line: 4, column: 1 }), "Duplicate schema definition: The schema is defined more than once. Use extend schema {...} to add to the schema definition".to_owned()),
+            (Some(Pos { line: 3, column: 3 }), "Duplicate operation type: The query operation type is defined more than once in the schema definition".to_owned()),
+        ]
+    );
+
+    let error = parse_schema("type Query { a: Int }\nextend schema @dir(arg: 1 {}")
+        .expect_err("invalid extend schema should not parse");
+    assert_eq!(
+        error.position,
+        Some(Pos {
+            line: 2,
+            column: 27
+        })
+    );
+}
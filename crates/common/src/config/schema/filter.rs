@@ -0,0 +1,251 @@
+use super::{ObjectFieldDefinition, TypeDef};
+use crate::config::{config_file::DeprecatedFilter, FilterConfig};
+use ndc_models::{FunctionName, ProcedureName, TypeName};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Remove types excluded by the filter, or referenced but not supported, along with any fields and arguments referencing them.
+/// Input objects with a required field of an excluded type are excluded too, as are fields and root fields
+/// with a required argument of an excluded type.
+/// Deprecated fields and optional arguments are removed if the filter excludes them.
+/// Finally, types that were reachable from root fields before filtering but no longer are, are removed.
+pub fn filter_definitions(
+    filter: &FilterConfig,
+    root_type_references: &BTreeSet<TypeName>,
+    unsupported_types: &BTreeSet<TypeName>,
+    query_fields: &mut BTreeMap<FunctionName, ObjectFieldDefinition>,
+    mutation_fields: &mut BTreeMap<ProcedureName, ObjectFieldDefinition>,
+    definitions: &mut BTreeMap<TypeName, TypeDef>,
+) {
+    let reachable_before = reachable_types(root_type_references.iter().cloned(), definitions);
+    let exclude_deprecated = filter.deprecated == DeprecatedFilter::Exclude;
+
+    let mut excluded: BTreeSet<TypeName> = definitions
+        .keys()
+        .filter(|name| !filter.types.includes(name.inner()))
+        .chain(unsupported_types)
+        .cloned()
+        .collect();
+
+    // excluding a type may make input objects that require it impossible to construct
+    loop {
+        let newly_excluded: Vec<TypeName> = definitions
+            .iter()
+            .filter(|(name, _)| !excluded.contains(*name))
+            .filter(|(_, typedef)| match typedef {
+                TypeDef::InputObject { fields, .. } => fields
+                    .values()
+                    .any(|field| field.is_required() && excluded.contains(&field.r#type.name())),
+                _ => false,
+            })
+            .map(|(name, _)| name.to_owned())
+            .collect();
+
+        if newly_excluded.is_empty() {
+            break;
+        }
+
+        excluded.extend(newly_excluded);
+    }
+
+    definitions.retain(|name, _| !excluded.contains(name));
+
+    for typedef in definitions.values_mut() {
+        match typedef {
+            TypeDef::Object { fields, .. } => {
+                fields.retain(|_, field| retain_field(field, &excluded, exclude_deprecated));
+            }
+            TypeDef::InputObject { fields, .. } => {
+                fields.retain(|_, field| {
+                    let hidden = exclude_deprecated
+                        && field.deprecation_reason.is_some()
+                        && !field.is_required();
+                    !hidden && !excluded.contains(&field.r#type.name())
+                });
+            }
+            TypeDef::Scalar { .. } | TypeDef::Enum { .. } => {}
+        }
+    }
+
+    query_fields.retain(|_, field| retain_field(field, &excluded, exclude_deprecated));
+    mutation_fields.retain(|_, field| retain_field(field, &excluded, exclude_deprecated));
+
+    let root_type_references = query_fields
+        .values()
+        .chain(mutation_fields.values())
+        .flat_map(field_type_references);
+    let reachable_after = reachable_types(root_type_references, definitions);
+
+    definitions
+        .retain(|name, _| reachable_after.contains(name) || !reachable_before.contains(name));
+}
+
+/// Whether to keep a field, removing any optional arguments of excluded types, or that are deprecated if excluding deprecated fields
+fn retain_field(
+    field: &mut ObjectFieldDefinition,
+    excluded: &BTreeSet<TypeName>,
+    exclude_deprecated: bool,
+) -> bool {
+    if excluded.contains(&field.r#type.name())
+        || (exclude_deprecated && field.deprecation_reason.is_some())
+    {
+        return false;
+    }
+
+    let requires_excluded_type = field
+        .arguments
+        .values()
+        .any(|argument| argument.is_required() && excluded.contains(&argument.r#type.name()));
+
+    if requires_excluded_type {
+        return false;
+    }
+
+    field.arguments.retain(|_, argument| {
+        let hidden =
+            exclude_deprecated && argument.deprecation_reason.is_some() && !argument.is_required();
+        !hidden && !excluded.contains(&argument.r#type.name())
+    });
+
+    true
+}
+
+fn field_type_references(field: &ObjectFieldDefinition) -> impl Iterator<Item = TypeName> + '_ {
+    std::iter::once(field.r#type.name()).chain(
+        field
+            .arguments
+            .values()
+            .map(|argument| argument.r#type.name()),
+    )
+}
+
+fn reachable_types(
+    roots: impl Iterator<Item = TypeName>,
+    definitions: &BTreeMap<TypeName, TypeDef>,
+) -> BTreeSet<TypeName> {
+    let mut reachable = BTreeSet::new();
+    let mut pending: Vec<TypeName> = roots.collect();
+
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+
+        match definitions.get(&name) {
+            Some(TypeDef::Object { fields, .. }) => {
+                pending.extend(fields.values().flat_map(field_type_references));
+            }
+            Some(TypeDef::InputObject { fields, .. }) => {
+                pending.extend(fields.values().map(|field| field.r#type.name()));
+            }
+            Some(TypeDef::Scalar { .. } | TypeDef::Enum { .. }) | None => {}
+        }
+    }
+
+    reachable
+}
+
+#[test]
+fn test_filter_definitions() {
+    let schema_document = super::parse_schema(
+        r#"
+        type Query {
+          user(id: ID!): User
+          users(where: UserFilter, legacyOrder: String @deprecated(reason: "Use orderBy")): [User!]!
+          userByEmail(email: String!): User @deprecated
+          admin_audit_log: [AuditEntry!]!
+        }
+        type Mutation {
+          createUser(name: String!): User
+          admin_deleteUser(id: ID!): Boolean
+          admin_purge(filter: PurgeFilter!): Boolean
+        }
+        type User {
+          id: ID!
+          name: String!
+          secret: Secret
+          legacyId: Int @deprecated(reason: "Use id")
+        }
+        type Secret {
+          value: String!
+        }
+        input UserFilter {
+          name: String
+          secret: SecretFilter
+        }
+        input SecretFilter {
+          value: String!
+        }
+        input PurgeFilter {
+          olderThan: Int!
+        }
+        type AuditEntry {
+          message: String!
+          at: Timestamp!
+        }
+        scalar Timestamp
+        scalar Unused
+        "#,
+    )
+    .unwrap();
+
+    let filter = FilterConfig {
+        mutations: crate::config::NameFilter {
+            include: None,
+            exclude: vec!["admin_*".to_string()],
+        },
+        queries: crate::config::NameFilter {
+            include: Some(vec!["user*".to_string()]),
+            exclude: vec![],
+        },
+        types: crate::config::NameFilter {
+            include: None,
+            exclude: vec!["Secret*".to_string()],
+        },
+        deprecated: DeprecatedFilter::Exclude,
+    };
+
+    let schema = super::SchemaDefinition::new(
+        &schema_document,
+        &crate::config::SchemaConfig {
+            filter,
+            ..crate::config::SchemaConfig::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        schema
+            .query_fields
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["user", "users"]
+    );
+    assert_eq!(
+        schema
+            .mutation_fields
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["createUser"]
+    );
+
+    let Some(TypeDef::Object { fields, .. }) = schema.definitions.get("User") else {
+        panic!("User should be an object type");
+    };
+    assert!(!fields.contains_key("secret"));
+    assert!(!fields.contains_key("legacyId"));
+
+    let users = schema.query_fields.get("users").unwrap();
+    assert!(!users.arguments.contains_key("legacyOrder"));
+
+    let Some(TypeDef::InputObject { fields, .. }) = schema.definitions.get("UserFilter") else {
+        panic!("UserFilter should be an input object type");
+    };
+    assert!(!fields.contains_key("secret"));
+
+    // excluded types, and types only reachable through filtered root fields, are removed.
+    // Types that were never reachable are left as-is.
+    let type_names: Vec<String> = schema.definitions.keys().map(ToString::to_string).collect();
+    assert_eq!(type_names, ["ID", "String", "Unused", "User", "UserFilter"]);
+}
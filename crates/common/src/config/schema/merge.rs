@@ -0,0 +1,625 @@
+use super::{LocatedSchemaDefinitionError, SchemaDefinitionError};
+use graphql_parser::{schema, Pos};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+/// Directive marking schema definitions parsed from `extend schema`.
+/// Names starting with `__` are reserved by GraphQL, so it cannot clash with directives in the document.
+const SCHEMA_EXTENSION_DIRECTIVE: &str = "__schemaExtension";
+
+/// A schema document with all type extensions applied to their base definitions.
+/// Schema extensions are kept apart, as they may extend the default schema definition when there is no explicit one.
+pub struct MergedDocument<'a> {
+    pub schema_definition: Option<schema::SchemaDefinition<'a, String>>,
+    pub schema_extensions: Vec<schema::SchemaDefinition<'a, String>>,
+    pub type_definitions: BTreeMap<String, schema::TypeDefinition<'a, String>>,
+}
+
+impl<'a> MergedDocument<'a> {
+    /// Merge all definitions and extensions in the document.
+    /// Problems are pushed to `errors`, and the offending definitions are skipped,
+    /// so that validation can continue and report as many problems as possible at once.
+    pub fn new(
+        schema_document: &schema::Document<'a, String>,
+        errors: &mut Vec<LocatedSchemaDefinitionError>,
+    ) -> Self {
+        let mut schema_definition: Option<schema::SchemaDefinition<'a, String>> = None;
+        let mut schema_extensions = vec![];
+        let mut type_definitions = BTreeMap::new();
+        let mut type_extensions = vec![];
+
+        for definition in &schema_document.definitions {
+            match definition {
+                schema::Definition::SchemaDefinition(definition) => {
+                    let mut definition = definition.clone();
+                    if unmark_schema_extension(&mut definition) {
+                        schema_extensions.push(definition);
+                    } else if schema_definition.is_some() {
+                        errors.push(
+                            SchemaDefinitionError::DuplicateSchemaDefinition
+                                .at(definition.position),
+                        );
+                    } else {
+                        schema_definition = Some(definition);
+                    }
+                }
+                schema::Definition::TypeDefinition(definition) => {
+                    let name = type_definition_name(definition);
+                    if type_definitions.contains_key(name) {
+                        errors.push(
+                            SchemaDefinitionError::DuplicateTypeDefinition(name.to_owned().into())
+                                .at(type_definition_position(definition)),
+                        );
+                    } else {
+                        type_definitions.insert(name.to_owned(), definition.clone());
+                    }
+                }
+                schema::Definition::TypeExtension(extension) => type_extensions.push(extension),
+                schema::Definition::DirectiveDefinition(_) => {}
+            }
+        }
+
+        // extensions may appear before the type they extend, so we only apply them once all definitions are known
+        for extension in type_extensions {
+            let name = type_extension_name(extension);
+            match type_definitions.get_mut(name) {
+                Some(definition) => merge_type_extension(definition, extension, errors),
+                None => errors.push(
+                    SchemaDefinitionError::TypeExtensionNotFound(name.to_owned().into())
+                        .at(type_extension_position(extension)),
+                ),
+            }
+        }
+
+        for definition in type_definitions.values() {
+            check_unique_members(definition, errors);
+        }
+
+        Self {
+            schema_definition,
+            schema_extensions,
+            type_definitions,
+        }
+    }
+}
+
+/// Add the operation types and directives of an `extend schema` to the schema definition
+pub fn merge_schema_extension<'a>(
+    merged: &mut schema::SchemaDefinition<'a, String>,
+    extension: &schema::SchemaDefinition<'a, String>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) {
+    for (operation, merged_type, extension_type) in [
+        ("query", &mut merged.query, &extension.query),
+        ("mutation", &mut merged.mutation, &extension.mutation),
+        (
+            "subscription",
+            &mut merged.subscription,
+            &extension.subscription,
+        ),
+    ] {
+        match (&merged_type, extension_type) {
+            (Some(_), Some(_)) => errors.push(
+                SchemaDefinitionError::DuplicateOperationType(operation.to_owned())
+                    .at(extension.position),
+            ),
+            (None, Some(extension_type)) => *merged_type = Some(extension_type.to_owned()),
+            (_, None) => {}
+        }
+    }
+
+    merged
+        .directives
+        .extend(extension.directives.iter().cloned());
+}
+
+fn merge_type_extension<'a>(
+    definition: &mut schema::TypeDefinition<'a, String>,
+    extension: &schema::TypeExtension<'a, String>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) {
+    match (definition, extension) {
+        (schema::TypeDefinition::Scalar(definition), schema::TypeExtension::Scalar(extension)) => {
+            definition
+                .directives
+                .extend(extension.directives.iter().cloned());
+        }
+        (schema::TypeDefinition::Object(definition), schema::TypeExtension::Object(extension)) => {
+            definition
+                .implements_interfaces
+                .extend(extension.implements_interfaces.iter().cloned());
+            definition
+                .directives
+                .extend(extension.directives.iter().cloned());
+            definition.fields.extend(extension.fields.iter().cloned());
+        }
+        (
+            schema::TypeDefinition::Interface(definition),
+            schema::TypeExtension::Interface(extension),
+        ) => {
+            definition
+                .implements_interfaces
+                .extend(extension.implements_interfaces.iter().cloned());
+            definition
+                .directives
+                .extend(extension.directives.iter().cloned());
+            definition.fields.extend(extension.fields.iter().cloned());
+        }
+        (schema::TypeDefinition::Union(definition), schema::TypeExtension::Union(extension)) => {
+            definition
+                .directives
+                .extend(extension.directives.iter().cloned());
+            definition.types.extend(extension.types.iter().cloned());
+        }
+        (schema::TypeDefinition::Enum(definition), schema::TypeExtension::Enum(extension)) => {
+            definition
+                .directives
+                .extend(extension.directives.iter().cloned());
+            definition.values.extend(extension.values.iter().cloned());
+        }
+        (
+            schema::TypeDefinition::InputObject(definition),
+            schema::TypeExtension::InputObject(extension),
+        ) => {
+            definition
+                .directives
+                .extend(extension.directives.iter().cloned());
+            definition.fields.extend(extension.fields.iter().cloned());
+        }
+        (definition, extension) => errors.push(
+            SchemaDefinitionError::TypeExtensionKindMismatch {
+                type_name: type_extension_name(extension).to_owned().into(),
+                definition_kind: type_definition_kind(definition),
+                extension_kind: type_extension_kind(extension),
+            }
+            .at(type_extension_position(extension)),
+        ),
+    }
+}
+
+/// Fields, enum values and union members must be unique within a type, including any added by extensions.
+fn check_unique_members(
+    definition: &schema::TypeDefinition<'_, String>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) {
+    let members: Vec<(&String, Pos)> = match definition {
+        schema::TypeDefinition::Scalar(_) => vec![],
+        schema::TypeDefinition::Object(object) => object
+            .fields
+            .iter()
+            .map(|field| (&field.name, field.position))
+            .collect(),
+        schema::TypeDefinition::Interface(interface) => interface
+            .fields
+            .iter()
+            .map(|field| (&field.name, field.position))
+            .collect(),
+        // union members have no position of their own
+        schema::TypeDefinition::Union(union) => union
+            .types
+            .iter()
+            .map(|member| (member, union.position))
+            .collect(),
+        schema::TypeDefinition::Enum(enum_definition) => enum_definition
+            .values
+            .iter()
+            .map(|value| (&value.name, value.position))
+            .collect(),
+        schema::TypeDefinition::InputObject(input_object) => input_object
+            .fields
+            .iter()
+            .map(|field| (&field.name, field.position))
+            .collect(),
+    };
+
+    let mut seen = BTreeSet::new();
+
+    for (member, position) in members {
+        if !seen.insert(member) {
+            errors.push(
+                SchemaDefinitionError::DuplicateTypeMember {
+                    type_name: type_definition_name(definition).to_owned().into(),
+                    member: member.to_owned(),
+                }
+                .at(position),
+            );
+        }
+    }
+}
+
+pub fn type_definition_name<'b>(definition: &'b schema::TypeDefinition<'_, String>) -> &'b str {
+    match definition {
+        schema::TypeDefinition::Scalar(scalar) => &scalar.name,
+        schema::TypeDefinition::Object(object) => &object.name,
+        schema::TypeDefinition::Interface(interface) => &interface.name,
+        schema::TypeDefinition::Union(union) => &union.name,
+        schema::TypeDefinition::Enum(enum_definition) => &enum_definition.name,
+        schema::TypeDefinition::InputObject(input_object) => &input_object.name,
+    }
+}
+
+fn type_extension_name<'b>(extension: &'b schema::TypeExtension<'_, String>) -> &'b str {
+    match extension {
+        schema::TypeExtension::Scalar(scalar) => &scalar.name,
+        schema::TypeExtension::Object(object) => &object.name,
+        schema::TypeExtension::Interface(interface) => &interface.name,
+        schema::TypeExtension::Union(union) => &union.name,
+        schema::TypeExtension::Enum(enum_extension) => &enum_extension.name,
+        schema::TypeExtension::InputObject(input_object) => &input_object.name,
+    }
+}
+
+pub fn type_definition_position(definition: &schema::TypeDefinition<'_, String>) -> Pos {
+    match definition {
+        schema::TypeDefinition::Scalar(scalar) => scalar.position,
+        schema::TypeDefinition::Object(object) => object.position,
+        schema::TypeDefinition::Interface(interface) => interface.position,
+        schema::TypeDefinition::Union(union) => union.position,
+        schema::TypeDefinition::Enum(enum_definition) => enum_definition.position,
+        schema::TypeDefinition::InputObject(input_object) => input_object.position,
+    }
+}
+
+fn type_extension_position(extension: &schema::TypeExtension<'_, String>) -> Pos {
+    match extension {
+        schema::TypeExtension::Scalar(scalar) => scalar.position,
+        schema::TypeExtension::Object(object) => object.position,
+        schema::TypeExtension::Interface(interface) => interface.position,
+        schema::TypeExtension::Union(union) => union.position,
+        schema::TypeExtension::Enum(enum_extension) => enum_extension.position,
+        schema::TypeExtension::InputObject(input_object) => input_object.position,
+    }
+}
+
+fn type_definition_kind(definition: &schema::TypeDefinition<'_, String>) -> &'static str {
+    match definition {
+        schema::TypeDefinition::Scalar(_) => "scalar",
+        schema::TypeDefinition::Object(_) => "type",
+        schema::TypeDefinition::Interface(_) => "interface",
+        schema::TypeDefinition::Union(_) => "union",
+        schema::TypeDefinition::Enum(_) => "enum",
+        schema::TypeDefinition::InputObject(_) => "input",
+    }
+}
+
+fn type_extension_kind(extension: &schema::TypeExtension<'_, String>) -> &'static str {
+    match extension {
+        schema::TypeExtension::Scalar(_) => "scalar",
+        schema::TypeExtension::Object(_) => "type",
+        schema::TypeExtension::Interface(_) => "interface",
+        schema::TypeExtension::Union(_) => "union",
+        schema::TypeExtension::Enum(_) => "enum",
+        schema::TypeExtension::InputObject(_) => "input",
+    }
+}
+
+/// graphql-parser does not support `extend schema`, and fails to parse documents that use it.
+/// Split every `extend schema` outside of strings and comments from the document, returning the document without them,
+/// and the position of each extension along with its source rewritten as a `schema` definition.
+/// Removed text is replaced with spaces, so line and column positions are kept and parse errors point at the original document.
+pub fn split_schema_extensions(schema_string: &str) -> (String, Vec<(Pos, String)>) {
+    let ranges = schema_extension_ranges(schema_string.as_bytes());
+
+    let document_string = blank_outside(schema_string, |offset| {
+        !ranges.iter().any(|range| range.contains(&offset))
+    });
+
+    let extensions = ranges
+        .iter()
+        .map(|range| {
+            // drop the `extend` keyword, keeping `schema` and everything after it
+            let definition = range.start + "extend".len()..range.end;
+            let mut extension_string =
+                blank_outside(schema_string, |offset| definition.contains(&offset));
+
+            // operation types are optional in extensions, but not in schema definitions
+            if !schema_string[..range.end].ends_with('}') {
+                extension_string.push_str(" {}");
+            }
+
+            (byte_position(schema_string, range.start), extension_string)
+        })
+        .collect();
+
+    (document_string, extensions)
+}
+
+/// Mark a schema definition parsed from `extend schema`, so it is merged into the schema definition
+/// instead of being reported as a duplicate
+pub fn mark_schema_extension(definition: &mut schema::SchemaDefinition<'_, String>) {
+    definition.directives.push(schema::Directive {
+        position: definition.position,
+        name: SCHEMA_EXTENSION_DIRECTIVE.to_owned(),
+        arguments: vec![],
+    });
+}
+
+/// Remove the schema extension marker, returning whether the definition was marked
+fn unmark_schema_extension(definition: &mut schema::SchemaDefinition<'_, String>) -> bool {
+    let directive_count = definition.directives.len();
+    definition
+        .directives
+        .retain(|directive| directive.name != SCHEMA_EXTENSION_DIRECTIVE);
+    definition.directives.len() != directive_count
+}
+
+/// Replace all characters at byte offsets not kept with spaces, except line terminators
+fn blank_outside(schema_string: &str, keep: impl Fn(usize) -> bool) -> String {
+    schema_string
+        .char_indices()
+        .map(|(offset, char)| match char {
+            '\n' | '\r' => char,
+            _ if keep(offset) => char,
+            _ => ' ',
+        })
+        .collect()
+}
+
+/// Byte ranges of all `extend schema` definitions outside of strings and comments,
+/// from the `extend` keyword to the end of the operation types, or of the last directive
+fn schema_extension_ranges(bytes: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'#' => index = skip_comment(bytes, index),
+            b'"' => index = skip_string(bytes, index),
+            byte if is_name_start(byte) => {
+                let (name, end) = read_name(bytes, index);
+
+                if name == b"extend" {
+                    let next = skip_ignored(bytes, end);
+                    if next < bytes.len() && is_name_start(bytes[next]) {
+                        let (next_name, next_end) = read_name(bytes, next);
+                        if next_name == b"schema" {
+                            let extension_end = skip_schema_extension_body(bytes, next_end);
+                            ranges.push(index..extension_end);
+                            index = extension_end;
+                            continue;
+                        }
+                    }
+                }
+
+                index = end;
+            }
+            _ => index += 1,
+        }
+    }
+
+    ranges
+}
+
+/// Skip the directives and operation types following `extend schema`, returning the offset after the last of them
+fn skip_schema_extension_body(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    let mut index = skip_ignored(bytes, start);
+
+    while index < bytes.len() && bytes[index] == b'@' {
+        let name_start = skip_ignored(bytes, index + 1);
+        let (_, name_end) = read_name(bytes, name_start);
+        end = name_end;
+        index = skip_ignored(bytes, name_end);
+
+        if index < bytes.len() && bytes[index] == b'(' {
+            end = skip_delimited(bytes, index, b'(', b')');
+            index = skip_ignored(bytes, end);
+        }
+    }
+
+    if index < bytes.len() && bytes[index] == b'{' {
+        end = skip_delimited(bytes, index, b'{', b'}');
+    }
+
+    end
+}
+
+/// Skip from an opening delimiter past its matching closing delimiter, ignoring any in strings and comments
+fn skip_delimited(bytes: &[u8], start: usize, open: u8, close: u8) -> usize {
+    let mut depth = 0;
+    let mut index = start;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'#' => index = skip_comment(bytes, index),
+            b'"' => index = skip_string(bytes, index),
+            byte => {
+                index += 1;
+                if byte == open {
+                    depth += 1;
+                } else if byte == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    index.min(bytes.len())
+}
+
+/// Skip a string or block string starting at a quote
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut index = start;
+
+    if bytes[index..].starts_with(b"\"\"\"") {
+        index += 3;
+        while index < bytes.len() && !bytes[index..].starts_with(b"\"\"\"") {
+            // escaped triple quotes do not end a block string
+            index += if bytes[index..].starts_with(b"\\\"\"\"") {
+                4
+            } else {
+                1
+            };
+        }
+        index += 3;
+    } else {
+        index += 1;
+        while index < bytes.len() && bytes[index] != b'"' && bytes[index] != b'\n' {
+            index += if bytes[index] == b'\\' { 2 } else { 1 };
+        }
+        index += 1;
+    }
+
+    index.min(bytes.len())
+}
+
+/// line and column of a byte offset, counted the way graphql-parser does, starting at 1
+fn byte_position(schema_string: &str, offset: usize) -> Pos {
+    let preceding = &schema_string[..offset];
+    let line_start = preceding.rfind('\n').map_or(0, |index| index + 1);
+
+    Pos {
+        line: preceding.matches('\n').count() + 1,
+        column: preceding[line_start..].chars().count() + 1,
+    }
+}
+
+fn is_name_start(byte: u8) -> bool {
+    byte == b'_' || byte.is_ascii_alphabetic()
+}
+
+fn read_name(bytes: &[u8], start: usize) -> (&[u8], usize) {
+    let mut end = start;
+    while end < bytes.len() && (bytes[end] == b'_' || bytes[end].is_ascii_alphanumeric()) {
+        end += 1;
+    }
+    (&bytes[start..end], end)
+}
+
+fn skip_comment(bytes: &[u8], start: usize) -> usize {
+    let mut index = start;
+    while index < bytes.len() && bytes[index] != b'\n' && bytes[index] != b'\r' {
+        index += 1;
+    }
+    index
+}
+
+/// skip whitespace, line terminators, commas and comments, which are all insignificant in GraphQL
+fn skip_ignored(bytes: &[u8], start: usize) -> usize {
+    let mut index = start;
+    while index < bytes.len() {
+        match bytes[index] {
+            b' ' | b'\t' | b'\n' | b'\r' | b',' => index += 1,
+            b'#' => index = skip_comment(bytes, index),
+            _ => break,
+        }
+    }
+    index
+}
+
+#[test]
+fn test_merge_type_extensions() {
+    let schema_string = r"
+        schema { query: Query }
+        type Query { a: Int }
+        extend type Query { b: Int }
+        enum Color { RED }
+        extend enum Color { GREEN }
+    ";
+
+    let document =
+        graphql_parser::parse_schema::<String>(schema_string).expect("test schema should parse");
+    let mut errors = vec![];
+    let merged = MergedDocument::new(&document, &mut errors);
+    assert!(errors.is_empty(), "extensions should merge: {errors:?}");
+
+    let schema_definition = merged
+        .schema_definition
+        .expect("schema definition should be kept");
+    assert_eq!(schema_definition.query.as_deref(), Some("Query"));
+
+    match merged.type_definitions.get("Query") {
+        Some(schema::TypeDefinition::Object(query)) => assert_eq!(
+            query
+                .fields
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        ),
+        _ => panic!("Query should be an object type"),
+    }
+    match merged.type_definitions.get("Color") {
+        Some(schema::TypeDefinition::Enum(color)) => assert_eq!(color.values.len(), 2),
+        _ => panic!("Color should be an enum type"),
+    }
+}
+
+#[test]
+fn test_split_schema_extensions() {
+    let (document_string, extensions) = split_schema_extensions(
+        "\"extend schema is not a keyword in strings\"\n# extend schema\ntype Query { a: Int }",
+    );
+    assert!(extensions.is_empty());
+    assert!(document_string.contains("extend schema"));
+
+    let schema_string = "type Query { a: Int }\n  extend schema @dir(arg: \"}\") # comment\nextend\n  schema { mutation: Mutation } type Mutation { b: Int }";
+    let (document_string, extensions) = split_schema_extensions(schema_string);
+    assert_eq!(
+        document_string.lines().map(str::trim).collect::<Vec<_>>(),
+        vec![
+            "type Query { a: Int }",
+            "# comment",
+            "",
+            "type Mutation { b: Int }"
+        ]
+    );
+    // removed text is blanked out, so the remaining definitions keep their positions
+    assert_eq!(
+        document_string.find("type Mutation"),
+        schema_string.find("type Mutation")
+    );
+
+    assert_eq!(
+        extensions
+            .iter()
+            .map(|(position, extension_string)| (
+                *position,
+                extension_string
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                Pos { line: 2, column: 3 },
+                "schema @dir(arg: \"}\") {}".to_owned()
+            ),
+            (
+                Pos { line: 3, column: 1 },
+                "schema { mutation: Mutation }".to_owned()
+            ),
+        ]
+    );
+    assert_eq!(
+        extensions[1].1.find("schema"),
+        schema_string.find("schema { mutation")
+    );
+}
+
+#[test]
+fn test_duplicate_definitions() {
+    for schema_string in [
+        "type Query { a: Int } type Query { b: Int }",
+        "type Query { a: Int } extend type Query { a: Int }",
+        "type Query { a: Int } extend input Query { b: Int }",
+        "extend type Query { a: Int }",
+        "schema { query: Query } schema { query: Query } type Query { a: Int }",
+    ] {
+        let document = graphql_parser::parse_schema::<String>(schema_string)
+            .expect("test schema should parse");
+        let mut errors = vec![];
+        MergedDocument::new(&document, &mut errors);
+        assert_eq!(
+            errors.len(),
+            1,
+            "Schema {schema_string} should fail to merge with one error"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use super::{
+    scalars::default_scalar_representation, LocatedSchemaDefinitionError, SchemaDefinitionError,
+    TypeDef,
+};
+use graphql_parser::{schema, Pos};
+use ndc_models::TypeName;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter,
+};
+
+/// Scalars every GraphQL schema has, whether or not they are declared in the schema document
+pub(super) const BUILT_IN_SCALARS: [&str; 5] = ["String", "Int", "Float", "Boolean", "ID"];
+
+/// Check that every type referenced by a field, argument or input field is defined.
+/// Built-in scalars that are referenced but not declared are added to `definitions`.
+/// Of the root types, only `root_fields` are checked, so root fields excluded by filters are not.
+/// Interface and union types are defined but not supported, and root types are defined but not object types.
+/// References to them are not errors, instead the referenced interface and union types, and root types,
+/// are returned, so fields and arguments referencing them can be removed.
+pub fn check_type_references<'a, 'b>(
+    type_definitions: &'b BTreeMap<String, schema::TypeDefinition<'a, String>>,
+    root_fields: &[(&String, &'b schema::Field<'a, String>)],
+    definitions: &mut BTreeMap<TypeName, TypeDef>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) -> (BTreeSet<TypeName>, BTreeSet<TypeName>) {
+    let mut references: Vec<(String, &schema::Type<'a, String>, Pos)> = vec![];
+    let mut unsupported_types = BTreeSet::new();
+    let mut root_types = BTreeSet::new();
+
+    for (type_name, field) in root_fields {
+        references.extend(field_references(type_name, field));
+    }
+
+    for type_definition in type_definitions.values() {
+        match type_definition {
+            schema::TypeDefinition::Object(object)
+                if definitions.contains_key(object.name.as_str()) =>
+            {
+                for field in &object.fields {
+                    references.extend(field_references(&object.name, field));
+                }
+            }
+            schema::TypeDefinition::InputObject(input_object) => {
+                for field in &input_object.fields {
+                    references.push((
+                        format!("{}.{}", input_object.name, field.name),
+                        &field.value_type,
+                        field.position,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (referenced_by, type_reference, position) in references {
+        let type_name = named_type(type_reference);
+
+        if definitions.contains_key(type_name.as_str()) {
+            continue;
+        }
+
+        if matches!(
+            type_definitions.get(type_name),
+            Some(schema::TypeDefinition::Interface(_) | schema::TypeDefinition::Union(_))
+        ) {
+            unsupported_types.insert(type_name.to_owned().into());
+            continue;
+        }
+
+        // object types missing from the definitions are root types, eg. a Relay payload field `query: Query`
+        if matches!(
+            type_definitions.get(type_name),
+            Some(schema::TypeDefinition::Object(_))
+        ) {
+            root_types.insert(type_name.to_owned().into());
+            continue;
+        }
+
+        if BUILT_IN_SCALARS.contains(&type_name.as_str())
+            && !type_definitions.contains_key(type_name)
+        {
+            definitions.insert(
+                type_name.to_owned().into(),
+                TypeDef::Scalar {
+                    representation: default_scalar_representation(type_name),
+                    description: None,
+                },
+            );
+            continue;
+        }
+
+        errors.push(
+            SchemaDefinitionError::UnknownTypeReference {
+                type_name: type_name.to_owned().into(),
+                referenced_by,
+            }
+            .at(position),
+        );
+    }
+
+    (unsupported_types, root_types)
+}
+
+/// References by the field type and argument types of an object field
+fn field_references<'a, 'b>(
+    type_name: &str,
+    field: &'b schema::Field<'a, String>,
+) -> impl Iterator<Item = (String, &'b schema::Type<'a, String>, Pos)> + 'b {
+    let type_name = type_name.to_owned();
+
+    iter::once((
+        format!("{type_name}.{}", field.name),
+        &field.field_type,
+        field.position,
+    ))
+    .chain(field.arguments.iter().map(move |argument| {
+        (
+            format!("{type_name}.{}({})", field.name, argument.name),
+            &argument.value_type,
+            argument.position,
+        )
+    }))
+}
+
+fn named_type<'b>(type_reference: &'b schema::Type<'_, String>) -> &'b String {
+    match type_reference {
+        schema::Type::NamedType(name) => name,
+        schema::Type::ListType(underlying) | schema::Type::NonNullType(underlying) => {
+            named_type(underlying)
+        }
+    }
+}
+
+#[test]
+fn test_type_references() {
+    let schema_document = super::parse_schema(
+        r"
+        type Query {
+          a(filter: Filter): [Item!]!
+          b: Node
+          legacy: Legacy
+        }
+        type Item { id: ID!, name: String }
+        input Filter { name: String, item: Unknown }
+        interface Node { id: ID! }
+        ",
+    )
+    .expect("schema should parse");
+
+    let filter = crate::config::FilterConfig {
+        queries: crate::config::NameFilter {
+            include: None,
+            exclude: vec!["legacy".to_owned()],
+        },
+        ..Default::default()
+    };
+
+    let errors = super::SchemaDefinition::new(
+        &schema_document,
+        &crate::config::SchemaConfig {
+            filter,
+            ..crate::config::SchemaConfig::default()
+        },
+    )
+    .expect_err("schema should be invalid");
+
+    let errors: Vec<String> = errors.0.iter().map(ToString::to_string).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            "schema.graphql:8:38: Unknown type reference: Filter.item references type Unknown, which is not defined",
+        ]
+    );
+
+    let schema_document =
+        super::parse_schema("type Query { a: [Int!] }").expect("schema should parse");
+
+    let schema =
+        super::SchemaDefinition::new(&schema_document, &crate::config::SchemaConfig::default())
+            .expect("schema should be valid");
+
+    assert!(matches!(
+        schema.definitions.get("Int"),
+        Some(TypeDef::Scalar {
+            representation: crate::config::config_file::ScalarRepresentation::Int32,
+            description: None
+        })
+    ));
+}
+
+#[test]
+fn test_unsupported_type_references() {
+    let schema_document = super::parse_schema(
+        r"
+        type Query {
+          node(id: ID!): Node
+          search(term: String!): [SearchResult!]!
+          user(id: ID!): User
+        }
+        interface Node { id: ID! }
+        union SearchResult = User
+        type User implements Node { id: ID!, name: String, friends: [Node!] }
+        ",
+    )
+    .expect("schema should parse");
+
+    let schema =
+        super::SchemaDefinition::new(&schema_document, &crate::config::SchemaConfig::default())
+            .expect("interface and union references should not be errors");
+
+    assert_eq!(
+        schema
+            .query_fields
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["user"]
+    );
+    let Some(TypeDef::Object { fields, .. }) = schema.definitions.get("User") else {
+        panic!("User should be an object type");
+    };
+    assert!(!fields.contains_key("friends"));
+    assert_eq!(
+        schema.describe_unsupported_types().as_deref(),
+        Some("Interface and union types are not supported, fields and arguments referencing these types were removed: Node, SearchResult")
+    );
+}
+
+#[test]
+fn test_root_type_references() {
+    let schema_document = super::parse_schema(
+        r"
+        type Query { user(id: ID!): User, root: Query! }
+        type Mutation { createUser(name: String!): CreateUserPayload }
+        type CreateUserPayload { user: User, query: Query }
+        type User { id: ID!, name: String }
+        ",
+    )
+    .expect("schema should parse");
+
+    let schema =
+        super::SchemaDefinition::new(&schema_document, &crate::config::SchemaConfig::default())
+            .expect("root type references should not be errors");
+
+    assert_eq!(
+        schema
+            .query_fields
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["user"]
+    );
+    let Some(TypeDef::Object { fields, .. }) = schema.definitions.get("CreateUserPayload") else {
+        panic!("CreateUserPayload should be an object type");
+    };
+    assert_eq!(
+        fields.keys().map(ToString::to_string).collect::<Vec<_>>(),
+        ["user"]
+    );
+    assert_eq!(
+        schema.describe_unsupported_types().as_deref(),
+        Some("Root types cannot be used as field types, fields referencing these types were removed: Query")
+    );
+}
@@ -0,0 +1,303 @@
+use super::{
+    references::BUILT_IN_SCALARS, LocatedSchemaDefinitionError, SchemaDefinitionError, TypeDef,
+};
+use crate::config::config_file::ScalarRepresentation;
+use graphql_parser::schema;
+use ndc_models::{ScalarTypeName, TypeName};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Infer a scalar representation from the `@specifiedBy` url if present, falling back to the scalar name.
+/// Used to seed the configuration, where the inferred representation can be reviewed.
+pub fn infer_scalar_representation(name: &str, specified_by: Option<&str>) -> ScalarRepresentation {
+    specified_by
+        .and_then(specified_by_representation)
+        .or_else(|| well_known_name_representation(name))
+        .unwrap_or_else(|| default_scalar_representation(name))
+}
+
+/// Representation of scalars not listed in the configuration.
+/// Guess the type representation by common GraphQL scalar names such as String, Int, Float, Boolean, etc...
+/// https://spec.graphql.org/draft/#sec-Scalars.Built-in-Scalars
+/// This mapping is deliberately conservative, and must not change, as that would change the schema of existing connectors.
+pub fn default_scalar_representation(name: &str) -> ScalarRepresentation {
+    match name.to_lowercase().as_str() {
+        "bool" | "boolean" => ScalarRepresentation::Boolean,
+        "int8" | "tinyint" => ScalarRepresentation::Int8,
+        "int16" | "smallint" => ScalarRepresentation::Int16,
+        "int" | "int32" | "serial" => ScalarRepresentation::Int32,
+        "int64" => ScalarRepresentation::Int64,
+        "bigint" | "bigserial" => ScalarRepresentation::BigInteger,
+        "float8" | "float16" | "float32" => ScalarRepresentation::Float32,
+        "float" | "float64" => ScalarRepresentation::Float64,
+        "date" => ScalarRepresentation::Date,
+        "timestamptz" | "datetime" => ScalarRepresentation::TimestampTz,
+        "id" | "string" | "uuid" | "text" | "citext" | "varchar" => ScalarRepresentation::String,
+        _ => ScalarRepresentation::Json,
+    }
+}
+
+/// Representations for all scalars in a schema document, as seeded into the configuration by the CLI
+pub fn default_scalar_representations(
+    schema_document: &schema::Document<'_, String>,
+) -> BTreeMap<ScalarTypeName, ScalarRepresentation> {
+    schema_document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            schema::Definition::TypeDefinition(schema::TypeDefinition::Scalar(scalar)) => Some((
+                scalar.name.to_owned().into(),
+                infer_scalar_representation(&scalar.name, specified_by_url(&scalar.directives)),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Names of the scalars a schema document defines, including built-in scalars, which need not be declared
+pub fn defined_scalar_names<'b>(
+    schema_document: &'b schema::Document<'_, String>,
+) -> BTreeSet<&'b str> {
+    schema_document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            schema::Definition::TypeDefinition(schema::TypeDefinition::Scalar(scalar)) => {
+                Some(scalar.name.as_str())
+            }
+            _ => None,
+        })
+        .chain(BUILT_IN_SCALARS)
+        .collect()
+}
+
+pub fn specified_by_url<'b>(directives: &'b [schema::Directive<'_, String>]) -> Option<&'b str> {
+    directives
+        .iter()
+        .find(|directive| directive.name == "specifiedBy")
+        .and_then(|directive| {
+            directive
+                .arguments
+                .iter()
+                .find_map(|(name, value)| match value {
+                    schema::Value::String(url) if name == "url" => Some(url.as_str()),
+                    _ => None,
+                })
+        })
+}
+
+/// Apply representations from configuration, which must refer to scalar types in the schema.
+pub fn apply_scalar_representations(
+    scalars: &BTreeMap<ScalarTypeName, ScalarRepresentation>,
+    definitions: &mut BTreeMap<TypeName, TypeDef>,
+    errors: &mut Vec<LocatedSchemaDefinitionError>,
+) {
+    for (name, configured_representation) in scalars {
+        match definitions.get_mut(name.inner()) {
+            Some(TypeDef::Scalar { representation, .. }) => {
+                if let ScalarRepresentation::Enum { one_of } = configured_representation {
+                    if one_of.is_empty() {
+                        errors.push(LocatedSchemaDefinitionError {
+                            position: None,
+                            error: SchemaDefinitionError::EmptyScalarEnumRepresentation(
+                                name.to_owned(),
+                            ),
+                        });
+                    }
+                }
+                configured_representation.clone_into(representation);
+            }
+            _ => errors.push(LocatedSchemaDefinitionError {
+                position: None,
+                error: SchemaDefinitionError::ScalarRepresentationTypeNotFound(name.to_owned()),
+            }),
+        }
+    }
+}
+
+/// Representation of scalars specified by a known scalars.graphql.org specification or RFC.
+/// Only exact urls are matched, as a url merely mentioning a format may specify something else, eg. a local date time.
+fn specified_by_representation(url: &str) -> Option<ScalarRepresentation> {
+    let url = normalize_url(url);
+
+    let representation = match url.as_str() {
+        "scalars.graphql.org/andimarek/date-time" => ScalarRepresentation::TimestampTz,
+        "scalars.graphql.org/andimarek/local-date-time" => ScalarRepresentation::Timestamp,
+        "scalars.graphql.org/andimarek/local-date" => ScalarRepresentation::Date,
+        _ => match rfc_number(&url)? {
+            "3339" => ScalarRepresentation::TimestampTz,
+            "4122" | "9562" => ScalarRepresentation::Uuid,
+            "3986" | "3987" => ScalarRepresentation::String,
+            "7946" => ScalarRepresentation::Geography,
+            "4648" => ScalarRepresentation::Bytes,
+            _ => return None,
+        },
+    };
+
+    Some(representation)
+}
+
+/// Lowercase, without scheme, `www.`, fragment, trailing slash, or `.html` / `.txt` extension
+fn normalize_url(url: &str) -> String {
+    let url = url.trim().to_lowercase();
+    let url = url.split('#').next().unwrap_or_default();
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    let url = url.trim_end_matches('/');
+    let url = url
+        .strip_suffix(".html")
+        .or_else(|| url.strip_suffix(".txt"))
+        .unwrap_or(url);
+
+    url.to_owned()
+}
+
+/// The number of an RFC from its url on the IETF or RFC Editor sites
+fn rfc_number(url: &str) -> Option<&str> {
+    let document = [
+        "tools.ietf.org/html/",
+        "datatracker.ietf.org/doc/html/",
+        "datatracker.ietf.org/doc/",
+        "rfc-editor.org/rfc/",
+    ]
+    .iter()
+    .find_map(|prefix| url.strip_prefix(prefix))?;
+
+    document
+        .strip_prefix("rfc")
+        .filter(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Common scalar names not covered by the default mapping
+fn well_known_name_representation(name: &str) -> Option<ScalarRepresentation> {
+    match name.to_lowercase().as_str() {
+        "long" => Some(ScalarRepresentation::Int64),
+        "bigdecimal" | "decimal" | "numeric" => Some(ScalarRepresentation::BigDecimal),
+        "timestamp" | "localdatetime" => Some(ScalarRepresentation::Timestamp),
+        "uuid" => Some(ScalarRepresentation::Uuid),
+        "bytes" | "bytea" => Some(ScalarRepresentation::Bytes),
+        "geography" => Some(ScalarRepresentation::Geography),
+        "geometry" => Some(ScalarRepresentation::Geometry),
+        "url" | "uri" | "email" => Some(ScalarRepresentation::String),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_infer_scalar_representation() {
+    assert_eq!(
+        infer_scalar_representation("Int", None),
+        ScalarRepresentation::Int32
+    );
+    assert_eq!(
+        infer_scalar_representation("DateTime", None),
+        ScalarRepresentation::TimestampTz
+    );
+    assert_eq!(
+        infer_scalar_representation(
+            "Instant",
+            Some("https://scalars.graphql.org/andimarek/date-time")
+        ),
+        ScalarRepresentation::TimestampTz
+    );
+    assert_eq!(
+        infer_scalar_representation("uuid", None),
+        ScalarRepresentation::Uuid
+    );
+    assert_eq!(
+        default_scalar_representation("uuid"),
+        ScalarRepresentation::String
+    );
+    assert_eq!(
+        infer_scalar_representation("Whatever", Some("https://example.com")),
+        ScalarRepresentation::Json
+    );
+}
+
+#[test]
+fn test_specified_by_representation() {
+    for (url, representation) in [
+        (
+            "https://scalars.graphql.org/andimarek/date-time",
+            Some(ScalarRepresentation::TimestampTz),
+        ),
+        (
+            "https://scalars.graphql.org/andimarek/local-date-time.html",
+            Some(ScalarRepresentation::Timestamp),
+        ),
+        (
+            "https://scalars.graphql.org/andimarek/local-date/",
+            Some(ScalarRepresentation::Date),
+        ),
+        (
+            "https://tools.ietf.org/html/rfc3339",
+            Some(ScalarRepresentation::TimestampTz),
+        ),
+        (
+            "https://datatracker.ietf.org/doc/html/rfc4122#section-3",
+            Some(ScalarRepresentation::Uuid),
+        ),
+        (
+            "https://www.rfc-editor.org/rfc/rfc9562.html",
+            Some(ScalarRepresentation::Uuid),
+        ),
+        (
+            "https://datatracker.ietf.org/doc/rfc7946",
+            Some(ScalarRepresentation::Geography),
+        ),
+        ("https://example.com/iso8601", None),
+        ("https://example.com/rfc3339-but-local", None),
+        ("https://tools.ietf.org/html/rfc1234", None),
+        ("https://scalars.graphql.org/andimarek/local-time", None),
+    ] {
+        assert_eq!(specified_by_representation(url), representation, "{url}");
+    }
+
+    // unknown urls fall back to the scalar name
+    assert_eq!(
+        infer_scalar_representation("LocalDateTime", Some("https://example.com/iso8601")),
+        ScalarRepresentation::Timestamp
+    );
+}
+
+#[test]
+fn test_scalar_representation_config() {
+    let schema_document = super::parse_schema("type Query { a: DateTime } scalar DateTime")
+        .expect("schema should parse");
+    let scalars = BTreeMap::from_iter(vec![
+        (
+            "DateTime".to_string().into(),
+            ScalarRepresentation::TimestampTz,
+        ),
+        ("Query".to_string().into(), ScalarRepresentation::Json),
+        (
+            "Missing".to_string().into(),
+            ScalarRepresentation::Enum { one_of: vec![] },
+        ),
+    ]);
+
+    let errors = super::SchemaDefinition::new(
+        &schema_document,
+        &crate::config::SchemaConfig {
+            scalars,
+            ..crate::config::SchemaConfig::default()
+        },
+    )
+    .expect_err("scalar configuration should be invalid");
+
+    let errors: Vec<_> = errors
+        .0
+        .iter()
+        .map(|error| error.error.config_node_path())
+        .collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            Some(vec!["scalars".to_string(), "Missing".to_string()]),
+            Some(vec!["scalars".to_string(), "Query".to_string()]),
+        ]
+    );
+}
@@ -0,0 +1,858 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, future::Future};
+
+use crate::{
+    client::{execute_graphql, get_http_client},
+    config::ConnectionConfig,
+    oauth2::TokenCache,
+};
+use graphql_parser::{
+    query::{self, OperationDefinition, Selection, Type, Value},
+    schema::{
+        Definition, Directive, DirectiveDefinition, DirectiveLocation, Document, EnumType,
+        EnumValue, Field, InputObjectType, InputValue, InterfaceType, ObjectType, ScalarType,
+        SchemaDefinition, TypeDefinition, UnionType,
+    },
+    Pos,
+};
+
+use self::introspection::{
+    DirectivesIntrospection, InputTypeRef, Introspection, IntrospectionMetaTypes, MetaType,
+    OutputTypeRef, TypeIntrospection,
+};
+pub mod introspection;
+
+pub async fn execute_graphql_introspection(
+    connection: &ConnectionConfig,
+) -> Result<graphql_client::Response<Introspection>, Box<dyn Error>> {
+    let client = get_http_client(connection)?;
+
+    let mut headers = connection.headers.clone();
+    TokenCache::default()
+        .authorize(&mut headers, &client, connection)
+        .await?;
+
+    let features_query = include_str!("./graphql/introspection_features_query.graphql");
+
+    let (_, features) = execute_graphql::<IntrospectionMetaTypes>(
+        features_query,
+        BTreeMap::new(),
+        &connection.endpoint,
+        &headers,
+        &client,
+        &vec![],
+    )
+    .await?;
+
+    let features = features
+        .data
+        .map(|meta_types| IntrospectionFeatures::new(&meta_types))
+        .unwrap_or_default();
+
+    let (_, mut introspection) = execute_graphql::<Introspection>(
+        &introspection_query(&features),
+        BTreeMap::new(),
+        &connection.endpoint,
+        &headers,
+        &client,
+        &vec![],
+    )
+    .await?;
+
+    if let Some(introspection) = &mut introspection.data {
+        resolve_truncated_type_refs(introspection, &features, |query, variables| {
+            let client = &client;
+            let headers = &headers;
+            async move {
+                let (_, response) = execute_graphql::<serde_json::Value>(
+                    &query,
+                    variables,
+                    &connection.endpoint,
+                    headers,
+                    client,
+                    &vec![],
+                )
+                .await?;
+                Ok(response)
+            }
+        })
+        .await?;
+    }
+
+    Ok(introspection)
+}
+
+/// Depth of type references in the bundled introspection query, eg. `[[[String!]!]!]!` is 7 levels deep
+const TYPE_REF_DEPTH: usize = 8;
+/// Follow-up queries double the depth until all type references are complete, up to this depth
+const MAX_TYPE_REF_DEPTH: usize = 64;
+
+/// Re-introspect types and directives with type references nested deeper than the introspection query went,
+/// using follow-up queries with deeper type references.
+pub async fn resolve_truncated_type_refs<F, Fut>(
+    introspection: &mut Introspection,
+    features: &IntrospectionFeatures,
+    execute: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(String, BTreeMap<String, serde_json::Value>) -> Fut,
+    Fut: Future<Output = Result<graphql_client::Response<serde_json::Value>, Box<dyn Error>>>,
+{
+    let mut depth = TYPE_REF_DEPTH;
+
+    loop {
+        let truncated_types: Vec<String> = introspection
+            .schema
+            .types
+            .iter()
+            .filter(|typedef| typedef.has_truncated_type_refs())
+            .map(|typedef| typedef.name().to_owned())
+            .collect();
+        let truncated_directives = introspection
+            .schema
+            .directives
+            .iter()
+            .any(introspection::Directive::has_truncated_type_refs);
+
+        if truncated_types.is_empty() && !truncated_directives {
+            return Ok(());
+        }
+
+        if depth >= MAX_TYPE_REF_DEPTH {
+            return Err(format!(
+                "Type references in {} are nested more than {MAX_TYPE_REF_DEPTH} levels deep, which is not supported",
+                truncated_types.join(", ")
+            )
+            .into());
+        }
+
+        depth *= 2;
+
+        for name in truncated_types {
+            let variables = BTreeMap::from([("name".to_owned(), serde_json::json!(name))]);
+            let response = execute(type_query(features, depth), variables).await?;
+            let data: TypeIntrospection = follow_up_data(response, &format!("type {name}"), depth)?;
+            let typedef = data
+                .r#type
+                .ok_or_else(|| format!("Type {name} not found when introspecting it again"))?;
+
+            for existing in &mut introspection.schema.types {
+                if existing.name() == name {
+                    *existing = typedef;
+                    break;
+                }
+            }
+        }
+
+        if truncated_directives {
+            let response = execute(directives_query(features, depth), BTreeMap::new()).await?;
+            let data: DirectivesIntrospection = follow_up_data(response, "directives", depth)?;
+            introspection.schema.directives = data.schema.directives;
+        }
+    }
+}
+
+/// Data from a follow-up introspection query, which may fail where the initial query did not
+/// if the server limits query depth
+fn follow_up_data<T: serde::de::DeserializeOwned>(
+    response: graphql_client::Response<serde_json::Value>,
+    target: &str,
+    depth: usize,
+) -> Result<T, Box<dyn Error>> {
+    if let Some(errors) = response.errors {
+        return Err(format!(
+            "Introspection of {target} with type references {depth} levels deep failed, the server may limit query depth: {}",
+            serde_json::to_string(&errors)?
+        )
+        .into());
+    }
+
+    let data = response
+        .data
+        .ok_or_else(|| format!("Introspection of {target} returned no data"))?;
+
+    Ok(serde_json::from_value(data)?)
+}
+
+/// A follow-up query for a single type, with type references of the given depth
+pub fn type_query(features: &IntrospectionFeatures, depth: usize) -> String {
+    let query = introspection_query(features);
+    format!(
+        "query IntrospectionTypeQuery($name: String!) {{\n  __type(name: $name) {{\n    ...FullType\n  }}\n}}\n\n{}{}{}",
+        query_fragment(&query, "FullType"),
+        query_fragment(&query, "InputValue"),
+        type_ref_fragment(depth)
+    )
+}
+
+/// A follow-up query for directives, with type references of the given depth
+pub fn directives_query(features: &IntrospectionFeatures, depth: usize) -> String {
+    let query = introspection_query(features);
+    format!(
+        "query IntrospectionDirectivesQuery {{\n  __schema {{\n    directives {{\n      ...Directive\n    }}\n  }}\n}}\n\n{}{}{}",
+        query_fragment(&query, "Directive"),
+        query_fragment(&query, "InputValue"),
+        type_ref_fragment(depth)
+    )
+}
+
+/// A fragment of the bundled introspection query, including the trailing blank line
+fn query_fragment<'a>(query: &'a str, name: &str) -> &'a str {
+    let start = query
+        .find(&format!("fragment {name} on"))
+        .expect("fragment should be in the bundled introspection query");
+    let end = query[start..]
+        .find("\nfragment ")
+        .map_or(query.len(), |end| start + end + 1);
+    &query[start..end]
+}
+
+/// The `TypeRef` fragment, following `ofType` to the given depth
+fn type_ref_fragment(depth: usize) -> String {
+    let mut fragment = "fragment TypeRef on __Type {\n".to_owned();
+    for level in 1..=depth {
+        let indent = "  ".repeat(level);
+        fragment.push_str(&format!("{indent}kind\n{indent}name\n"));
+        if level < depth {
+            fragment.push_str(&format!("{indent}ofType {{\n"));
+        }
+    }
+    for level in (1..depth).rev() {
+        fragment.push_str(&format!("{}}}\n", "  ".repeat(level)));
+    }
+    fragment.push_str("}\n");
+    fragment
+}
+
+/// Introspection features added to the spec after the original release.
+/// Querying for them fails on servers that don't support them, so only those the server supports are requested.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IntrospectionFeatures {
+    /// `__Type.specifiedByURL`, October 2021
+    pub specified_by_url: bool,
+    /// `__Directive.isRepeatable`, October 2021
+    pub repeatable_directives: bool,
+    /// deprecation of arguments and input fields, October 2021
+    pub deprecated_input_values: bool,
+    /// `__Type.isOneOf`
+    pub one_of: bool,
+}
+
+impl IntrospectionFeatures {
+    pub fn new(meta_types: &IntrospectionMetaTypes) -> Self {
+        let has_field = |meta_type: &Option<MetaType>, field: &str| {
+            meta_type
+                .as_ref()
+                .is_some_and(|meta_type| meta_type.has_field(field))
+        };
+        let has_field_argument = |meta_type: &Option<MetaType>, field: &str, argument: &str| {
+            meta_type
+                .as_ref()
+                .is_some_and(|meta_type| meta_type.has_field_argument(field, argument))
+        };
+
+        Self {
+            specified_by_url: has_field(&meta_types.r#type, "specifiedByURL"),
+            repeatable_directives: has_field(&meta_types.directive, "isRepeatable"),
+            deprecated_input_values: has_field(&meta_types.input_value, "isDeprecated")
+                && has_field_argument(&meta_types.field, "args", "includeDeprecated")
+                && has_field_argument(&meta_types.directive, "args", "includeDeprecated")
+                && has_field_argument(&meta_types.r#type, "inputFields", "includeDeprecated"),
+            one_of: has_field(&meta_types.r#type, "isOneOf"),
+        }
+    }
+    /// All features, as requested by the bundled introspection query
+    pub fn all() -> Self {
+        Self {
+            specified_by_url: true,
+            repeatable_directives: true,
+            deprecated_input_values: true,
+            one_of: true,
+        }
+    }
+}
+
+/// The bundled introspection query, without any features the server does not support
+pub fn introspection_query(features: &IntrospectionFeatures) -> String {
+    let mut query = include_str!("./graphql/introspection_query.graphql").to_owned();
+
+    if !features.specified_by_url {
+        query = query.replace("  specifiedByURL\n", "");
+    }
+    if !features.repeatable_directives {
+        query = query.replace("  isRepeatable\n", "");
+    }
+    if !features.deprecated_input_values {
+        query = query
+            .replace("args(includeDeprecated: true)", "args")
+            .replace("inputFields(includeDeprecated: true)", "inputFields")
+            .replace(
+                "  defaultValue\n  isDeprecated\n  deprecationReason\n",
+                "  defaultValue\n",
+            );
+    }
+    if !features.one_of {
+        query = query.replace("  isOneOf\n", "");
+    }
+
+    query
+}
+
+/// graphql AST wants a position, but we don't actually use it.
+fn pos() -> Pos {
+    Pos { line: 0, column: 0 }
+}
+
+fn is_graphql_introspection_type(name: &str) -> bool {
+    [
+        "__Schema",
+        "__Type",
+        "__TypeKind",
+        "__Field",
+        "__InputValue",
+        "__EnumValue",
+        "__Directive",
+        "__DirectiveLocation",
+    ]
+    .contains(&name)
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntrospectionSchemaError {
+    InvalidDefaultValue {
+        name: String,
+        value: String,
+        message: String,
+    },
+    TruncatedTypeRef {
+        name: String,
+    },
+}
+
+impl Error for IntrospectionSchemaError {}
+
+impl Display for IntrospectionSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntrospectionSchemaError::InvalidDefaultValue {
+                name,
+                value,
+                message,
+            } => write!(
+                f,
+                "Default value {value} of {name} is not a valid GraphQL value: {message}"
+            ),
+            IntrospectionSchemaError::TruncatedTypeRef { name } => write!(
+                f,
+                "Type reference of {name} is truncated, truncated type references should be resolved before building the schema"
+            ),
+        }
+    }
+}
+
+pub fn schema_from_introspection(
+    introspection: Introspection,
+) -> Result<Document<'static, String>, IntrospectionSchemaError> {
+    let mut definitions = vec![];
+
+    definitions.push(Definition::SchemaDefinition(SchemaDefinition {
+        position: pos(),
+        directives: vec![], // todo
+        query: Some(introspection.schema.query_type.name),
+        mutation: introspection.schema.mutation_type.map(|t| t.name),
+        subscription: introspection.schema.subscription_type.map(|t| t.name),
+    }));
+
+    for typedef in introspection.schema.types {
+        if is_graphql_introspection_type(typedef.name()) {
+            continue;
+        }
+        let type_definition = match typedef {
+            introspection::TypeDef::Scalar(scalar) => TypeDefinition::Scalar(ScalarType {
+                position: pos(),
+                description: scalar.description,
+                name: scalar.name,
+                directives: scalar
+                    .specified_by_url
+                    .map(|url| Directive {
+                        position: pos(),
+                        name: "specifiedBy".to_owned(),
+                        arguments: vec![("url".to_owned(), Value::String(url))],
+                    })
+                    .into_iter()
+                    .collect(),
+            }),
+
+            introspection::TypeDef::Object(object) => TypeDefinition::Object(ObjectType {
+                position: pos(),
+                description: object.description,
+                name: object.name,
+                implements_interfaces: object.interfaces.into_iter().map(|i| i.name).collect(),
+                directives: vec![],
+                fields: object
+                    .fields
+                    .into_iter()
+                    .map(field_definition)
+                    .collect::<Result<_, _>>()?,
+            }),
+            introspection::TypeDef::InputObject(input) => {
+                TypeDefinition::InputObject(InputObjectType {
+                    position: pos(),
+                    description: input.description,
+                    name: input.name,
+                    directives: if input.is_one_of {
+                        vec![Directive {
+                            position: pos(),
+                            name: "oneOf".to_owned(),
+                            arguments: vec![],
+                        }]
+                    } else {
+                        vec![]
+                    },
+                    fields: input
+                        .input_fields
+                        .into_iter()
+                        .map(input_value)
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            introspection::TypeDef::Enum(enum_def) => TypeDefinition::Enum(EnumType {
+                position: pos(),
+                description: enum_def.description,
+                name: enum_def.name,
+                directives: vec![],
+                values: enum_def
+                    .enum_values
+                    .into_iter()
+                    .map(|val| EnumValue {
+                        position: pos(),
+                        description: val.description,
+                        name: val.name,
+                        directives: deprecated_directive(val.is_deprecated, val.deprecation_reason),
+                    })
+                    .collect(),
+            }),
+            introspection::TypeDef::Interface(interface) => {
+                TypeDefinition::Interface(InterfaceType {
+                    position: pos(),
+                    description: interface.description,
+                    name: interface.name,
+                    implements_interfaces: interface
+                        .interfaces
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|i| i.name)
+                        .collect(),
+                    directives: vec![],
+                    fields: interface
+                        .fields
+                        .into_iter()
+                        .map(field_definition)
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            introspection::TypeDef::Union(union) => TypeDefinition::Union(UnionType {
+                position: pos(),
+                description: union.description,
+                name: union.name,
+                directives: vec![],
+                types: union.possible_types.into_iter().map(|t| t.name).collect(),
+            }),
+        };
+
+        definitions.push(Definition::TypeDefinition(type_definition));
+    }
+
+    for directive in introspection.schema.directives {
+        definitions.push(Definition::DirectiveDefinition(DirectiveDefinition {
+            position: pos(),
+            description: directive.description,
+            name: directive.name,
+            arguments: directive
+                .args
+                .into_iter()
+                .map(input_value)
+                .collect::<Result<_, _>>()?,
+            repeatable: directive.is_repeatable,
+            locations: directive
+                .locations
+                .iter()
+                .map(|loc| match loc {
+                    introspection::DirectiveLocation::Query => DirectiveLocation::Query,
+                    introspection::DirectiveLocation::Mutation => DirectiveLocation::Mutation,
+                    introspection::DirectiveLocation::Subscription => {
+                        DirectiveLocation::Subscription
+                    }
+                    introspection::DirectiveLocation::Field => DirectiveLocation::Field,
+                    introspection::DirectiveLocation::FragmentDefinition => {
+                        DirectiveLocation::FragmentDefinition
+                    }
+                    introspection::DirectiveLocation::FragmentSpread => {
+                        DirectiveLocation::FragmentSpread
+                    }
+                    introspection::DirectiveLocation::InlineFragment => {
+                        DirectiveLocation::InlineFragment
+                    }
+                    introspection::DirectiveLocation::Schema => DirectiveLocation::Schema,
+                    introspection::DirectiveLocation::Scalar => DirectiveLocation::Scalar,
+                    introspection::DirectiveLocation::Object => DirectiveLocation::Object,
+                    introspection::DirectiveLocation::FieldDefinition => {
+                        DirectiveLocation::FieldDefinition
+                    }
+                    introspection::DirectiveLocation::ArgumentDefinition => {
+                        DirectiveLocation::ArgumentDefinition
+                    }
+                    introspection::DirectiveLocation::Interface => DirectiveLocation::Interface,
+                    introspection::DirectiveLocation::Union => DirectiveLocation::Union,
+                    introspection::DirectiveLocation::Enum => DirectiveLocation::Enum,
+                    introspection::DirectiveLocation::EnumValue => DirectiveLocation::EnumValue,
+                    introspection::DirectiveLocation::InputObject => DirectiveLocation::InputObject,
+                    introspection::DirectiveLocation::InputFieldDefinition => {
+                        DirectiveLocation::InputFieldDefinition
+                    }
+                    introspection::DirectiveLocation::VariableDefinition => {
+                        DirectiveLocation::VariableDefinition
+                    }
+                })
+                .collect(),
+        }));
+    }
+
+    Ok(graphql_parser::schema::Document { definitions })
+}
+
+fn field_definition(
+    field: introspection::Field,
+) -> Result<Field<'static, String>, IntrospectionSchemaError> {
+    Ok(Field {
+        position: pos(),
+        description: field.description,
+        arguments: field
+            .args
+            .into_iter()
+            .map(input_value)
+            .collect::<Result<_, _>>()?,
+        field_type: output_type(&field.name, field.r#type)?,
+        directives: deprecated_directive(field.is_deprecated, field.deprecation_reason),
+        name: field.name,
+    })
+}
+
+fn input_value(
+    input: introspection::InputValue,
+) -> Result<InputValue<'static, String>, IntrospectionSchemaError> {
+    Ok(InputValue {
+        position: pos(),
+        description: input.description,
+        value_type: input_type(&input.name, input.r#type)?,
+        default_value: input
+            .default_value
+            .map(|value| {
+                parse_value(&value).map_err(|message| {
+                    IntrospectionSchemaError::InvalidDefaultValue {
+                        name: input.name.clone(),
+                        value,
+                        message,
+                    }
+                })
+            })
+            .transpose()?,
+        directives: deprecated_directive(input.is_deprecated, input.deprecation_reason),
+        name: input.name,
+    })
+}
+
+fn deprecated_directive(
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+) -> Vec<Directive<'static, String>> {
+    if !is_deprecated {
+        return vec![];
+    }
+
+    vec![Directive {
+        position: pos(),
+        name: "deprecated".to_owned(),
+        arguments: deprecation_reason
+            .map(|reason| ("reason".to_owned(), Value::String(reason)))
+            .into_iter()
+            .collect(),
+    }]
+}
+
+/// Parse a GraphQL value, such as the default value of an argument
+fn parse_value(value: &str) -> Result<Value<'static, String>, String> {
+    // to parse a value using graphql parser, we build a dummy query
+    // this is a hack but it works, and this is not performance critical
+    let query_string = format!(r#"query {{ field(value: {value}) }}"#);
+
+    let document =
+        graphql_parser::parse_query::<'_, String>(&query_string).map_err(|err| err.to_string())?;
+
+    // a value that closes the dummy field early could parse into a different shape
+    let argument = match document.definitions.as_slice() {
+        [query::Definition::Operation(OperationDefinition::Query(query))] => {
+            match query.selection_set.items.as_slice() {
+                [Selection::Field(field)] => match field.arguments.as_slice() {
+                    [(_name, value)] => Some(value),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    argument
+        .map(|value| value.into_static().to_owned())
+        .ok_or_else(|| "expected a single value".to_owned())
+}
+
+#[test]
+fn test_parse_value() {
+    let values = vec![
+        "[ENUM_1, ENUM_2]",
+        "1.234",
+        r#""String""#,
+        r#"{object: "property"}"#,
+    ];
+
+    for value in values {
+        let parsed_value = parse_value(value).unwrap();
+        assert_eq!(
+            value,
+            parsed_value.to_string(),
+            "GraphQL value {value} should be parsed correctly"
+        );
+    }
+
+    for value in ["[ENUM_1", "1) other(value: 2", ""] {
+        assert!(parse_value(value).is_err(), "{value} should not parse");
+    }
+}
+
+#[test]
+fn test_directives() {
+    let introspection: Introspection = serde_json::from_value(serde_json::json!({
+        "__schema": {
+            "queryType": { "name": "Query" },
+            "mutationType": null,
+            "subscriptionType": null,
+            "types": [
+                {
+                    "kind": "OBJECT",
+                    "name": "Query",
+                    "description": null,
+                    "fields": [
+                        {
+                            "name": "user",
+                            "description": null,
+                            "args": [
+                                {
+                                    "name": "legacyId",
+                                    "description": null,
+                                    "type": { "kind": "SCALAR", "name": "Int" },
+                                    "defaultValue": null,
+                                    "isDeprecated": true,
+                                    "deprecationReason": "Use id"
+                                }
+                            ],
+                            "type": { "kind": "SCALAR", "name": "String" },
+                            "isDeprecated": true,
+                            "deprecationReason": null
+                        }
+                    ],
+                    "interfaces": []
+                },
+                {
+                    "kind": "INPUT_OBJECT",
+                    "name": "UserBy",
+                    "description": null,
+                    "inputFields": [
+                        {
+                            "name": "id",
+                            "description": null,
+                            "type": { "kind": "SCALAR", "name": "ID" },
+                            "defaultValue": null,
+                            "isDeprecated": false,
+                            "deprecationReason": null
+                        }
+                    ],
+                    "isOneOf": true
+                },
+                {
+                    "kind": "ENUM",
+                    "name": "Role",
+                    "description": null,
+                    "enumValues": [
+                        {
+                            "name": "ADMIN",
+                            "description": null,
+                            "isDeprecated": false,
+                            "deprecationReason": null
+                        },
+                        {
+                            "name": "ROOT",
+                            "description": null,
+                            "isDeprecated": true,
+                            "deprecationReason": "Use ADMIN"
+                        }
+                    ]
+                }
+            ],
+            "directives": []
+        }
+    }))
+    .expect("Introspection should deserialize");
+
+    let schema = schema_from_introspection(introspection)
+        .unwrap()
+        .to_string();
+
+    assert!(
+        schema.contains(r#"user(legacyId: Int @deprecated(reason: "Use id")): String @deprecated"#),
+        "{schema}"
+    );
+    assert!(schema.contains("  ADMIN\n"), "{schema}");
+    assert!(
+        schema.contains(r#"ROOT @deprecated(reason: "Use ADMIN")"#),
+        "{schema}"
+    );
+    assert!(schema.contains("input UserBy @oneOf {"), "{schema}");
+}
+
+#[test]
+fn test_invalid_introspection() {
+    let introspection = |arg: serde_json::Value| -> Introspection {
+        serde_json::from_value(serde_json::json!({
+            "__schema": {
+                "queryType": { "name": "Query" },
+                "types": [
+                    {
+                        "kind": "OBJECT",
+                        "name": "Query",
+                        "fields": [
+                            {
+                                "name": "users",
+                                "args": [arg],
+                                "type": { "kind": "SCALAR", "name": "String" },
+                                "isDeprecated": false
+                            }
+                        ],
+                        "interfaces": []
+                    }
+                ],
+                "directives": []
+            }
+        }))
+        .expect("Introspection should deserialize")
+    };
+
+    assert_eq!(
+        schema_from_introspection(introspection(serde_json::json!({
+            "name": "limit",
+            "type": { "kind": "SCALAR", "name": "Int" },
+            "defaultValue": "10) other(value: 2"
+        })))
+        .map_err(|err| err.to_string()),
+        Err("Default value 10) other(value: 2 of limit is not a valid GraphQL value: expected a single value".to_owned())
+    );
+    assert_eq!(
+        schema_from_introspection(introspection(serde_json::json!({
+            "name": "ids",
+            "type": { "kind": "NON_NULL", "ofType": { "kind": "LIST", "ofType": null } }
+        }))),
+        Err(IntrospectionSchemaError::TruncatedTypeRef {
+            name: "ids".to_owned()
+        })
+    );
+}
+
+#[test]
+fn test_introspection_query_features() {
+    let query = introspection_query(&IntrospectionFeatures::all());
+    assert_eq!(
+        query,
+        include_str!("./graphql/introspection_query.graphql"),
+        "all features should use the bundled query as-is"
+    );
+
+    let query = introspection_query(&IntrospectionFeatures::default());
+    for feature in [
+        "specifiedByURL",
+        "isRepeatable",
+        "args(includeDeprecated",
+        "inputFields(",
+        "isOneOf",
+    ] {
+        assert!(!query.contains(feature), "{feature} should be removed");
+    }
+    assert_eq!(query.matches("isDeprecated").count(), 2, "{query}");
+    graphql_parser::parse_query::<String>(&query).expect("query should remain valid");
+}
+
+#[test]
+fn test_follow_up_queries() {
+    let query = introspection_query(&IntrospectionFeatures::all());
+    assert_eq!(
+        query_fragment(&query, "TypeRef"),
+        type_ref_fragment(TYPE_REF_DEPTH),
+        "bundled query should have type references {TYPE_REF_DEPTH} levels deep"
+    );
+
+    for features in [
+        IntrospectionFeatures::all(),
+        IntrospectionFeatures::default(),
+    ] {
+        let query = type_query(&features, 16);
+        graphql_parser::parse_query::<String>(&query).expect("type query should be valid");
+        assert_eq!(query.matches("ofType").count(), 15);
+
+        let query = directives_query(&features, 16);
+        graphql_parser::parse_query::<String>(&query).expect("directives query should be valid");
+        assert!(!query.contains("FullType"));
+    }
+}
+
+fn input_type(
+    name: &str,
+    input: InputTypeRef,
+) -> Result<Type<'static, String>, IntrospectionSchemaError> {
+    let truncated = || IntrospectionSchemaError::TruncatedTypeRef {
+        name: name.to_owned(),
+    };
+    Ok(match input {
+        InputTypeRef::Scalar(named)
+        | InputTypeRef::InputObject(named)
+        | InputTypeRef::Enum(named) => Type::NamedType(named.name),
+        InputTypeRef::List(list) => Type::ListType(Box::new(input_type(
+            name,
+            *list.of_type.ok_or_else(truncated)?,
+        )?)),
+        InputTypeRef::NonNull(non_null) => Type::NonNullType(Box::new(input_type(
+            name,
+            *non_null.of_type.ok_or_else(truncated)?,
+        )?)),
+    })
+}
+fn output_type(
+    name: &str,
+    output: OutputTypeRef,
+) -> Result<Type<'static, String>, IntrospectionSchemaError> {
+    let truncated = || IntrospectionSchemaError::TruncatedTypeRef {
+        name: name.to_owned(),
+    };
+    Ok(match output {
+        OutputTypeRef::Scalar(named)
+        | OutputTypeRef::Object(named)
+        | OutputTypeRef::Enum(named)
+        | OutputTypeRef::Interface(named)
+        | OutputTypeRef::Union(named) => Type::NamedType(named.name),
+        OutputTypeRef::List(list) => Type::ListType(Box::new(output_type(
+            name,
+            *list.of_type.ok_or_else(truncated)?,
+        )?)),
+        OutputTypeRef::NonNull(non_null) => Type::NonNullType(Box::new(output_type(
+            name,
+            *non_null.of_type.ok_or_else(truncated)?,
+        )?)),
+    })
+}
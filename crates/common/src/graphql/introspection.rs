@@ -36,6 +36,10 @@ pub struct InputValue {
     #[serde(rename = "type")]
     pub r#type: InputTypeRef,
     pub default_value: Option<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    #[serde(default)]
+    pub deprecation_reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,6 +56,8 @@ pub struct EnumValue {
 pub struct Directive {
     pub name: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub is_repeatable: bool,
     pub locations: Vec<DirectiveLocation>,
     pub args: Vec<InputValue>,
 }
@@ -77,6 +83,7 @@ pub enum DirectiveLocation {
     EnumValue,
     InputObject,
     InputFieldDefinition,
+    VariableDefinition,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,6 +91,8 @@ pub enum DirectiveLocation {
 pub struct Scalar {
     pub name: String,
     pub description: Option<String>,
+    #[serde(rename = "specifiedByURL", default)]
+    pub specified_by_url: Option<String>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -122,17 +131,21 @@ pub struct InputObject {
     pub name: String,
     pub description: Option<String>,
     pub input_fields: Vec<InputValue>,
+    #[serde(default)]
+    pub is_one_of: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct List<T> {
-    pub of_type: Box<T>,
+    /// `None` if the introspection query did not go deep enough to reach the wrapped type
+    pub of_type: Option<Box<T>>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NonNull<T> {
-    pub of_type: Box<T>,
+    /// `None` if the introspection query did not go deep enough to reach the wrapped type
+    pub of_type: Option<Box<T>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -167,6 +180,70 @@ pub enum InputTypeRef {
     NonNull(NonNull<InputTypeRef>),
 }
 
+impl TypeDef {
+    pub fn name(&self) -> &str {
+        match self {
+            TypeDef::Scalar(Scalar { name, .. })
+            | TypeDef::Object(Object { name, .. })
+            | TypeDef::InputObject(InputObject { name, .. })
+            | TypeDef::Enum(Enum { name, .. })
+            | TypeDef::Interface(Interface { name, .. })
+            | TypeDef::Union(Union { name, .. }) => name,
+        }
+    }
+    /// Whether any type reference in this type was cut short by the depth of the introspection query
+    pub fn has_truncated_type_refs(&self) -> bool {
+        match self {
+            TypeDef::Object(Object { fields, .. })
+            | TypeDef::Interface(Interface { fields, .. }) => fields.iter().any(|field| {
+                field.r#type.is_truncated()
+                    || field.args.iter().any(|arg| arg.r#type.is_truncated())
+            }),
+            TypeDef::InputObject(InputObject { input_fields, .. }) => {
+                input_fields.iter().any(|field| field.r#type.is_truncated())
+            }
+            TypeDef::Scalar(_) | TypeDef::Enum(_) | TypeDef::Union(_) => false,
+        }
+    }
+}
+
+impl Directive {
+    /// Whether any argument type reference was cut short by the depth of the introspection query
+    pub fn has_truncated_type_refs(&self) -> bool {
+        self.args.iter().any(|arg| arg.r#type.is_truncated())
+    }
+}
+
+impl OutputTypeRef {
+    pub fn is_truncated(&self) -> bool {
+        match self {
+            OutputTypeRef::List(List { of_type }) | OutputTypeRef::NonNull(NonNull { of_type }) => {
+                of_type
+                    .as_ref()
+                    .is_none_or(|of_type| of_type.is_truncated())
+            }
+            OutputTypeRef::Scalar(_)
+            | OutputTypeRef::Object(_)
+            | OutputTypeRef::Enum(_)
+            | OutputTypeRef::Interface(_)
+            | OutputTypeRef::Union(_) => false,
+        }
+    }
+}
+
+impl InputTypeRef {
+    pub fn is_truncated(&self) -> bool {
+        match self {
+            InputTypeRef::List(List { of_type }) | InputTypeRef::NonNull(NonNull { of_type }) => {
+                of_type
+                    .as_ref()
+                    .is_none_or(|of_type| of_type.is_truncated())
+            }
+            InputTypeRef::Scalar(_) | InputTypeRef::InputObject(_) | InputTypeRef::Enum(_) => false,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ObjectTypeRef {
@@ -182,3 +259,66 @@ pub enum InterfaceTypeRef {
 pub struct NamedTypeRef {
     pub name: String,
 }
+
+/// Fields and arguments of the introspection meta types, used to detect support for introspection features
+/// added in later versions of the spec
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionMetaTypes {
+    #[serde(rename = "type")]
+    pub r#type: Option<MetaType>,
+    pub field: Option<MetaType>,
+    pub directive: Option<MetaType>,
+    pub input_value: Option<MetaType>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetaType {
+    pub fields: Option<Vec<MetaField>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetaField {
+    pub name: String,
+    pub args: Vec<MetaArgument>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetaArgument {
+    pub name: String,
+}
+
+impl MetaType {
+    fn field(&self, name: &str) -> Option<&MetaField> {
+        self.fields
+            .iter()
+            .flatten()
+            .find(|field| field.name == name)
+    }
+    pub fn has_field(&self, name: &str) -> bool {
+        self.field(name).is_some()
+    }
+    pub fn has_field_argument(&self, field: &str, argument: &str) -> bool {
+        self.field(field)
+            .is_some_and(|field| field.args.iter().any(|arg| arg.name == argument))
+    }
+}
+
+/// Response to a follow-up query for a single type
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeIntrospection {
+    #[serde(rename = "__type")]
+    pub r#type: Option<TypeDef>,
+}
+
+/// Response to a follow-up query for directives
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectivesIntrospection {
+    #[serde(rename = "__schema")]
+    pub schema: DirectivesSchema,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectivesSchema {
+    pub directives: Vec<Directive>,
+}
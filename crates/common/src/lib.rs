@@ -1,4 +1,8 @@
 pub mod capabilities;
 pub mod client;
+pub mod comparison;
 pub mod config;
+pub mod graphql;
+pub mod oauth2;
+pub mod schema_diff;
 pub mod schema_response;
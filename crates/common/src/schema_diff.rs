@@ -0,0 +1,516 @@
+use crate::config::schema::{
+    EnumValueDefinition, InputObjectFieldDefinition, ObjectFieldArgumentDefinition,
+    ObjectFieldDefinition, OperationType, SchemaDefinition, TypeDef, TypeRef,
+};
+use graphql_parser::schema::Value;
+use ndc_models::{ArgumentName, FieldName};
+use std::{collections::BTreeMap, fmt::Display};
+
+/// A difference between two schema definitions, from the point of view of the exposed NDC schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+    pub element: SchemaElement,
+    /// Path to the element, eg. `User.posts(limit:)` for an argument, using names in the target schema
+    pub path: String,
+    pub change: Change,
+    /// set if requests or responses valid against the old schema may fail against the new schema
+    pub breaking: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaElement {
+    RootField(OperationType),
+    Type,
+    Field,
+    Argument,
+    InputField,
+    EnumValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+    /// Type references are given with their default value, if any, eg. `Int! = 10`
+    TypeChanged {
+        from: String,
+        to: String,
+    },
+    KindChanged {
+        from: &'static str,
+        to: &'static str,
+    },
+    OneOfAdded,
+    OneOfRemoved,
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.element, self.path, self.change)
+    }
+}
+
+impl Display for SchemaElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaElement::RootField(OperationType::Query) => write!(f, "Query field"),
+            SchemaElement::RootField(OperationType::Mutation) => write!(f, "Mutation field"),
+            SchemaElement::Type => write!(f, "Type"),
+            SchemaElement::Field => write!(f, "Field"),
+            SchemaElement::Argument => write!(f, "Argument"),
+            SchemaElement::InputField => write!(f, "Input field"),
+            SchemaElement::EnumValue => write!(f, "Enum value"),
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added => write!(f, "was added"),
+            Change::Removed => write!(f, "was removed"),
+            Change::TypeChanged { from, to } => write!(f, "changed type from {from} to {to}"),
+            Change::KindChanged { from, to } => write!(f, "changed from {from} to {to}"),
+            Change::OneOfAdded => write!(f, "became a oneOf input object"),
+            Change::OneOfRemoved => write!(f, "is no longer a oneOf input object"),
+        }
+    }
+}
+
+/// Compare an old schema definition to a new one, eg. the configured schema to the live upstream schema.
+/// Both should be built with the same configuration, so only differences in the target schema are reported.
+pub fn diff_schemas(old: &SchemaDefinition, new: &SchemaDefinition) -> Vec<SchemaChange> {
+    let mut diff = SchemaDiff::default();
+
+    let query_type_name = old
+        .query_type_name
+        .as_ref()
+        .map_or("Query", |name| name.as_str());
+    diff.root_fields(
+        OperationType::Query,
+        query_type_name,
+        &old.query_fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+            .collect(),
+        &new.query_fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+            .collect(),
+    );
+    let mutation_type_name = old
+        .mutation_type_name
+        .as_ref()
+        .map_or("Mutation", |name| name.as_str());
+    diff.root_fields(
+        OperationType::Mutation,
+        mutation_type_name,
+        &old.mutation_fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+            .collect(),
+        &new.mutation_fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+            .collect(),
+    );
+
+    for (type_name, old_type) in &old.definitions {
+        match new.definitions.get(type_name) {
+            None => diff.push(SchemaElement::Type, type_name, Change::Removed, true),
+            Some(new_type) => diff.type_definition(type_name.as_str(), old_type, new_type),
+        }
+    }
+    for type_name in new.definitions.keys() {
+        if !old.definitions.contains_key(type_name) {
+            diff.push(SchemaElement::Type, type_name, Change::Added, false);
+        }
+    }
+
+    diff.changes
+}
+
+#[derive(Default)]
+struct SchemaDiff {
+    changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    fn push(&mut self, element: SchemaElement, path: impl Display, change: Change, breaking: bool) {
+        self.changes.push(SchemaChange {
+            element,
+            path: path.to_string(),
+            change,
+            breaking,
+        });
+    }
+    fn root_fields(
+        &mut self,
+        operation_type: OperationType,
+        type_name: &str,
+        old: &BTreeMap<&str, &ObjectFieldDefinition>,
+        new: &BTreeMap<&str, &ObjectFieldDefinition>,
+    ) {
+        let element = SchemaElement::RootField(operation_type);
+        for (field_name, old_field) in old {
+            match new.get(field_name) {
+                None => self.push(element, field_name, Change::Removed, true),
+                Some(new_field) => {
+                    self.field(element, type_name, field_name, old_field, new_field);
+                }
+            }
+        }
+        for field_name in new.keys() {
+            if !old.contains_key(field_name) {
+                self.push(element, field_name, Change::Added, false);
+            }
+        }
+    }
+    fn type_definition(&mut self, type_name: &str, old: &TypeDef, new: &TypeDef) {
+        match (old, new) {
+            (TypeDef::Scalar { .. }, TypeDef::Scalar { .. }) => {}
+            (TypeDef::Enum { values: old, .. }, TypeDef::Enum { values: new, .. }) => {
+                self.enum_values(type_name, old, new);
+            }
+            (TypeDef::Object { fields: old, .. }, TypeDef::Object { fields: new, .. }) => {
+                self.object_fields(type_name, old, new);
+            }
+            (
+                TypeDef::InputObject {
+                    fields: old,
+                    one_of: old_one_of,
+                    ..
+                },
+                TypeDef::InputObject {
+                    fields: new,
+                    one_of: new_one_of,
+                    ..
+                },
+            ) => {
+                match (old_one_of, new_one_of) {
+                    (false, true) => {
+                        self.push(SchemaElement::Type, type_name, Change::OneOfAdded, true);
+                    }
+                    (true, false) => {
+                        self.push(SchemaElement::Type, type_name, Change::OneOfRemoved, false);
+                    }
+                    _ => {}
+                }
+                self.input_fields(type_name, old, new);
+            }
+            _ => self.push(
+                SchemaElement::Type,
+                type_name,
+                Change::KindChanged {
+                    from: type_kind(old),
+                    to: type_kind(new),
+                },
+                true,
+            ),
+        }
+    }
+    fn enum_values(
+        &mut self,
+        type_name: &str,
+        old: &[EnumValueDefinition],
+        new: &[EnumValueDefinition],
+    ) {
+        let contains = |values: &[EnumValueDefinition], name: &str| {
+            values.iter().any(|value| value.name == name)
+        };
+        for value in old {
+            if !contains(new, &value.name) {
+                let path = format!("{type_name}.{}", value.name);
+                self.push(SchemaElement::EnumValue, path, Change::Removed, true);
+            }
+        }
+        for value in new {
+            if !contains(old, &value.name) {
+                let path = format!("{type_name}.{}", value.name);
+                self.push(SchemaElement::EnumValue, path, Change::Added, false);
+            }
+        }
+    }
+    fn object_fields(
+        &mut self,
+        type_name: &str,
+        old: &BTreeMap<FieldName, ObjectFieldDefinition>,
+        new: &BTreeMap<FieldName, ObjectFieldDefinition>,
+    ) {
+        for (field_name, old_field) in old {
+            match new.get(field_name) {
+                None => {
+                    let path = format!("{type_name}.{field_name}");
+                    self.push(SchemaElement::Field, path, Change::Removed, true);
+                }
+                Some(new_field) => self.field(
+                    SchemaElement::Field,
+                    type_name,
+                    field_name.as_str(),
+                    old_field,
+                    new_field,
+                ),
+            }
+        }
+        for field_name in new.keys() {
+            if !old.contains_key(field_name) {
+                let path = format!("{type_name}.{field_name}");
+                self.push(SchemaElement::Field, path, Change::Added, false);
+            }
+        }
+    }
+    fn field(
+        &mut self,
+        element: SchemaElement,
+        type_name: &str,
+        field_name: &str,
+        old: &ObjectFieldDefinition,
+        new: &ObjectFieldDefinition,
+    ) {
+        if old.r#type.to_string() != new.r#type.to_string() {
+            let path = match element {
+                SchemaElement::RootField(_) => field_name.to_owned(),
+                _ => format!("{type_name}.{field_name}"),
+            };
+            let change = Change::TypeChanged {
+                from: old.r#type.to_string(),
+                to: new.r#type.to_string(),
+            };
+            let breaking = !output_type_compatible(&old.r#type, &new.r#type);
+            self.push(element, path, change, breaking);
+        }
+        self.arguments(type_name, field_name, &old.arguments, &new.arguments);
+    }
+    fn arguments(
+        &mut self,
+        type_name: &str,
+        field_name: &str,
+        old: &BTreeMap<ArgumentName, ObjectFieldArgumentDefinition>,
+        new: &BTreeMap<ArgumentName, ObjectFieldArgumentDefinition>,
+    ) {
+        let path =
+            |argument_name: &ArgumentName| format!("{type_name}.{field_name}({argument_name}:)");
+        for (argument_name, old_argument) in old {
+            match new.get(argument_name) {
+                None => self.push(
+                    SchemaElement::Argument,
+                    path(argument_name),
+                    Change::Removed,
+                    true,
+                ),
+                Some(new_argument) => self.input_value(
+                    SchemaElement::Argument,
+                    path(argument_name),
+                    (&old_argument.r#type, old_argument.default_value.as_ref()),
+                    (&new_argument.r#type, new_argument.default_value.as_ref()),
+                    !old_argument.is_required() && new_argument.is_required(),
+                ),
+            }
+        }
+        for (argument_name, new_argument) in new {
+            if !old.contains_key(argument_name) {
+                self.push(
+                    SchemaElement::Argument,
+                    path(argument_name),
+                    Change::Added,
+                    new_argument.is_required(),
+                );
+            }
+        }
+    }
+    fn input_fields(
+        &mut self,
+        type_name: &str,
+        old: &BTreeMap<FieldName, InputObjectFieldDefinition>,
+        new: &BTreeMap<FieldName, InputObjectFieldDefinition>,
+    ) {
+        let path = |field_name: &FieldName| format!("{type_name}.{field_name}");
+        for (field_name, old_field) in old {
+            match new.get(field_name) {
+                None => self.push(
+                    SchemaElement::InputField,
+                    path(field_name),
+                    Change::Removed,
+                    true,
+                ),
+                Some(new_field) => self.input_value(
+                    SchemaElement::InputField,
+                    path(field_name),
+                    (&old_field.r#type, old_field.default_value.as_ref()),
+                    (&new_field.r#type, new_field.default_value.as_ref()),
+                    !old_field.is_required() && new_field.is_required(),
+                ),
+            }
+        }
+        for (field_name, new_field) in new {
+            if !old.contains_key(field_name) {
+                self.push(
+                    SchemaElement::InputField,
+                    path(field_name),
+                    Change::Added,
+                    new_field.is_required(),
+                );
+            }
+        }
+    }
+    fn input_value(
+        &mut self,
+        element: SchemaElement,
+        path: String,
+        (old_type, old_default): (&TypeRef, Option<&Value<'static, String>>),
+        (new_type, new_default): (&TypeRef, Option<&Value<'static, String>>),
+        became_required: bool,
+    ) {
+        let from = input_type_string(old_type, old_default);
+        let to = input_type_string(new_type, new_default);
+        if from != to {
+            let breaking = became_required || !input_type_compatible(old_type, new_type);
+            self.push(element, path, Change::TypeChanged { from, to }, breaking);
+        }
+    }
+}
+
+fn input_type_string(type_ref: &TypeRef, default_value: Option<&Value<'static, String>>) -> String {
+    match default_value {
+        Some(default_value) => format!("{type_ref} = {default_value}"),
+        None => type_ref.to_string(),
+    }
+}
+
+/// Values of the new output type are valid values of the old type, eg. `String!` where `String` was expected
+fn output_type_compatible(old: &TypeRef, new: &TypeRef) -> bool {
+    match (old, new) {
+        (TypeRef::NonNull(old), TypeRef::NonNull(new))
+        | (TypeRef::List(old), TypeRef::List(new)) => output_type_compatible(old, new),
+        (old, TypeRef::NonNull(new)) => output_type_compatible(old, new),
+        (TypeRef::Named(old), TypeRef::Named(new)) => old == new,
+        _ => false,
+    }
+}
+
+/// Values of the old input type are valid values of the new type, eg. `String` where `String!` was accepted
+fn input_type_compatible(old: &TypeRef, new: &TypeRef) -> bool {
+    match (old, new) {
+        (TypeRef::NonNull(old), TypeRef::NonNull(new))
+        | (TypeRef::List(old), TypeRef::List(new)) => input_type_compatible(old, new),
+        (TypeRef::NonNull(old), new) => input_type_compatible(old, new),
+        (TypeRef::Named(old), TypeRef::Named(new)) => old == new,
+        _ => false,
+    }
+}
+
+fn type_kind(type_definition: &TypeDef) -> &'static str {
+    match type_definition {
+        TypeDef::Scalar { .. } => "scalar",
+        TypeDef::Enum { .. } => "enum",
+        TypeDef::Object { .. } => "object",
+        TypeDef::InputObject { .. } => "input object",
+    }
+}
+
+#[test]
+fn test_diff_schemas() {
+    use crate::config::{schema::parse_schema, SchemaConfig};
+
+    let schema = |sdl: &str| {
+        SchemaDefinition::new(&parse_schema(sdl).unwrap(), &SchemaConfig::default()).unwrap()
+    };
+
+    let old = schema(
+        r"
+        type Query {
+          user(id: ID!, active: Boolean): User
+          users(limit: Int! = 10, offset: Int! = 0): [User]
+          legacyUsers: [User]
+        }
+        type User {
+          id: ID
+          name: String!
+          role: Role
+          email: String
+        }
+        enum Role { ADMIN EDITOR VIEWER }
+        input UserFilter { name: String }
+        ",
+    );
+    let new = schema(
+        r"
+        type Query {
+          user(id: ID, active: Boolean!): User
+          users(limit: Int!, offset: Int! = 5): [User]
+          search(term: String!): [User!]!
+        }
+        type User {
+          id: ID!
+          name: String
+          role: Role
+          createdAt: String
+        }
+        enum Role { ADMIN VIEWER OWNER }
+        scalar UserFilter
+        ",
+    );
+
+    let changes: Vec<String> = diff_schemas(&old, &new)
+        .iter()
+        .map(|change| {
+            let severity = if change.breaking { "breaking" } else { "safe" };
+            format!("{severity}: {change}")
+        })
+        .collect();
+
+    assert_eq!(
+        changes,
+        [
+            "breaking: Query field legacyUsers was removed",
+            "breaking: Argument Query.user(active:) changed type from Boolean to Boolean!",
+            "safe: Argument Query.user(id:) changed type from ID! to ID",
+            "breaking: Argument Query.users(limit:) changed type from Int! = 10 to Int!",
+            "safe: Argument Query.users(offset:) changed type from Int! = 0 to Int! = 5",
+            "safe: Query field search was added",
+            "breaking: Enum value Role.EDITOR was removed",
+            "safe: Enum value Role.OWNER was added",
+            "breaking: Field User.email was removed",
+            "safe: Field User.id changed type from ID to ID!",
+            "breaking: Field User.name changed type from String! to String",
+            "safe: Field User.createdAt was added",
+            "breaking: Type UserFilter changed from input object to scalar",
+        ]
+    );
+}
+
+#[test]
+fn test_diff_removed_scalar() {
+    use crate::config::{config_file::ScalarRepresentation, schema::parse_schema, SchemaConfig};
+
+    let mut schema_config = SchemaConfig {
+        scalars: BTreeMap::from([
+            ("ID".into(), ScalarRepresentation::Int64),
+            ("Money".into(), ScalarRepresentation::BigDecimal),
+        ]),
+        ..SchemaConfig::default()
+    };
+    let old = SchemaDefinition::new(
+        &parse_schema("type Query { id: ID balance: Money } scalar Money").unwrap(),
+        &schema_config,
+    )
+    .unwrap();
+
+    let new_document = parse_schema("type Query { id: ID }").unwrap();
+    // built-in scalars are defined whether or not they are declared
+    schema_config.retain_defined_scalars(&new_document);
+    assert_eq!(
+        schema_config.scalars,
+        BTreeMap::from([("ID".into(), ScalarRepresentation::Int64)])
+    );
+    let new = SchemaDefinition::new(&new_document, &schema_config).unwrap();
+
+    let changes: Vec<String> = diff_schemas(&old, &new)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        changes,
+        ["Query field balance was removed", "Type Money was removed"]
+    );
+}
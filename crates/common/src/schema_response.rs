@@ -1,20 +1,29 @@
-use crate::config::{
-    schema::{
-        InputObjectFieldDefinition, ObjectFieldArgumentDefinition, ObjectFieldDefinition,
-        SchemaDefinition, TypeDef, TypeRef,
+use crate::{
+    comparison::comparison_operators,
+    config::{
+        config_file::ScalarRepresentation,
+        schema::{
+            InputObjectFieldDefinition, ObjectFieldArgumentDefinition, ObjectFieldDefinition,
+            SchemaDefinition, TypeDef, TypeRef,
+        },
+        NamingConfig, RequestConfig, ResponseConfig,
     },
-    RequestConfig, ResponseConfig,
 };
+use graphql_parser::schema::Value;
 use ndc_models::{
-    self as models, ArgumentInfo, ArgumentName, FieldName, SchemaResponse, Type, TypeName,
-    TypeRepresentation,
+    self as models, ArgumentInfo, ArgumentName, FieldName, FunctionName, ProcedureName,
+    SchemaResponse, Type, TypeName,
 };
 use std::{collections::BTreeMap, iter};
 
+/// Input objects marked `@oneOf` have this constraint added to their description, as NDC cannot express it
+const ONE_OF_DESCRIPTION: &str = "Exactly one field must be set to a non-null value";
+
 pub fn schema_response(
     schema: &SchemaDefinition,
     request: &RequestConfig,
     response: &ResponseConfig,
+    naming: &NamingConfig,
 ) -> SchemaResponse {
     let forward_request_headers = !request.forward_headers.is_empty();
     let forward_response_headers = !response.forward_headers.is_empty();
@@ -24,29 +33,35 @@ pub fn schema_response(
         .iter()
         .filter_map(|(name, typedef)| match typedef {
             TypeDef::Object { .. } | TypeDef::InputObject { .. } => None,
-            TypeDef::Scalar { description: _ } => Some((
+            TypeDef::Scalar {
+                representation,
+                description: _,
+            } => Some((
                 name.to_owned().into(),
                 models::ScalarType {
-                    representation: type_name_to_representation(name),
+                    representation: representation.to_owned().into(),
                     aggregate_functions: BTreeMap::new(),
-                    comparison_operators: BTreeMap::new(),
+                    comparison_operators: comparison_operators(name, representation),
                     extraction_functions: BTreeMap::new(),
                 },
             )),
             TypeDef::Enum {
                 values,
                 description: _,
-            } => Some((
-                name.to_owned().into(),
-                models::ScalarType {
-                    representation: models::TypeRepresentation::Enum {
-                        one_of: values.iter().map(|value| value.name.to_owned()).collect(),
+            } => {
+                let representation = ScalarRepresentation::Enum {
+                    one_of: values.iter().map(|value| value.name.to_owned()).collect(),
+                };
+                Some((
+                    name.to_owned().into(),
+                    models::ScalarType {
+                        comparison_operators: comparison_operators(name, &representation),
+                        representation: representation.into(),
+                        aggregate_functions: BTreeMap::new(),
+                        extraction_functions: BTreeMap::new(),
                     },
-                    aggregate_functions: BTreeMap::new(),
-                    comparison_operators: BTreeMap::new(),
-                    extraction_functions: BTreeMap::new(),
-                },
-            )),
+                ))
+            }
         })
         .collect();
 
@@ -80,10 +95,17 @@ pub fn schema_response(
             TypeDef::InputObject {
                 fields,
                 description,
+                one_of,
             } => Some((
                 name.to_owned().into(),
                 models::ObjectType {
-                    description: description.to_owned(),
+                    description: match (description, one_of) {
+                        (description, false) => description.to_owned(),
+                        (None, true) => Some(ONE_OF_DESCRIPTION.to_owned()),
+                        (Some(description), true) => {
+                            Some(format!("{description}\n\n{ONE_OF_DESCRIPTION}"))
+                        }
+                    },
                     fields: fields.iter().map(map_input_object_field).collect(),
                     foreign_keys: BTreeMap::new(),
                 },
@@ -123,7 +145,8 @@ pub fn schema_response(
 
     let mut functions = vec![];
 
-    for (name, field) in &schema.query_fields {
+    for (name, _, field) in schema.function_fields() {
+        let name: FunctionName = naming.functions.ndc_name(name).into();
         let arguments = field.arguments.iter().map(map_argument);
         let arguments = if forward_request_headers {
             arguments
@@ -142,7 +165,7 @@ pub fn schema_response(
         };
 
         let result_type = if forward_response_headers {
-            let response_type_name = response.query_response_type_name(name);
+            let response_type_name = response.query_response_type_name(&name);
 
             object_types.insert(
                 response_type_name.to_owned().into(),
@@ -157,8 +180,8 @@ pub fn schema_response(
         };
 
         functions.push(models::FunctionInfo {
-            name: name.to_string().into(),
-            description: field.description.to_owned(),
+            name,
+            description: describe(&field.description, None, &field.deprecation_reason),
             arguments,
             result_type,
         });
@@ -166,7 +189,8 @@ pub fn schema_response(
 
     let mut procedures = vec![];
 
-    for (name, field) in &schema.mutation_fields {
+    for (name, _, field) in schema.procedure_fields() {
+        let name: ProcedureName = naming.procedures.ndc_name(name).into();
         let arguments = field.arguments.iter().map(map_argument);
         let arguments = if forward_request_headers {
             arguments
@@ -185,7 +209,7 @@ pub fn schema_response(
         };
 
         let result_type = if forward_response_headers {
-            let response_type_name = response.mutation_response_type_name(name);
+            let response_type_name = response.mutation_response_type_name(&name);
 
             object_types.insert(
                 response_type_name.to_owned().into(),
@@ -200,8 +224,8 @@ pub fn schema_response(
         };
 
         procedures.push(models::ProcedureInfo {
-            name: name.to_string().into(),
-            description: field.description.to_owned(),
+            name,
+            description: describe(&field.description, None, &field.deprecation_reason),
             arguments,
             result_type,
         });
@@ -229,7 +253,7 @@ pub fn schema_response(
         relational_query_arguments: BTreeMap::new(),
     };
 
-    models::SchemaResponse {
+    let mut schema_response = models::SchemaResponse {
         scalar_types,
         object_types,
         collections: vec![],
@@ -237,6 +261,84 @@ pub fn schema_response(
         procedures,
         capabilities: None,
         request_arguments: Some(request_level_arguments),
+    };
+
+    // types are renamed last, so generated types such as the headers and response types are not renamed
+    let type_names: BTreeMap<TypeName, TypeName> = schema
+        .definitions
+        .keys()
+        .map(|name| (name.to_owned(), naming.types.ndc_name(name.as_str()).into()))
+        .filter(|(upstream_name, ndc_name)| upstream_name != ndc_name)
+        .collect();
+
+    if !type_names.is_empty() {
+        rename_types(&mut schema_response, &type_names);
+    }
+
+    schema_response
+}
+
+fn rename_types(schema_response: &mut SchemaResponse, type_names: &BTreeMap<TypeName, TypeName>) {
+    let rename = |name: &str| {
+        type_names
+            .get(name)
+            .map_or_else(|| name.to_owned(), ToString::to_string)
+    };
+
+    schema_response.scalar_types = std::mem::take(&mut schema_response.scalar_types)
+        .into_iter()
+        .map(|(name, mut scalar_type)| {
+            for operator in scalar_type.comparison_operators.values_mut() {
+                if let models::ComparisonOperatorDefinition::Custom { argument_type } = operator {
+                    rename_type_references(argument_type, type_names);
+                }
+            }
+            (rename(name.as_str()).into(), scalar_type)
+        })
+        .collect();
+
+    schema_response.object_types = std::mem::take(&mut schema_response.object_types)
+        .into_iter()
+        .map(|(name, mut object_type)| {
+            for field in object_type.fields.values_mut() {
+                rename_type_references(&mut field.r#type, type_names);
+                for argument in field.arguments.values_mut() {
+                    rename_type_references(&mut argument.argument_type, type_names);
+                }
+            }
+            (rename(name.as_str()).into(), object_type)
+        })
+        .collect();
+
+    for function in &mut schema_response.functions {
+        rename_type_references(&mut function.result_type, type_names);
+        for argument in function.arguments.values_mut() {
+            rename_type_references(&mut argument.argument_type, type_names);
+        }
+    }
+
+    for procedure in &mut schema_response.procedures {
+        rename_type_references(&mut procedure.result_type, type_names);
+        for argument in procedure.arguments.values_mut() {
+            rename_type_references(&mut argument.argument_type, type_names);
+        }
+    }
+}
+
+fn rename_type_references(r#type: &mut Type, type_names: &BTreeMap<TypeName, TypeName>) {
+    match r#type {
+        Type::Named { name } => {
+            if let Some(ndc_name) = type_names.get(name) {
+                name.clone_from(ndc_name);
+            }
+        }
+        Type::Nullable { underlying_type } => rename_type_references(underlying_type, type_names),
+        Type::Array { element_type } => rename_type_references(element_type, type_names),
+        Type::Predicate { object_type_name } => {
+            if let Some(ndc_name) = type_names.get(object_type_name.as_str()) {
+                *object_type_name = ndc_name.as_str().into();
+            }
+        }
     }
 }
 
@@ -246,7 +348,7 @@ fn map_object_field(
     (
         name.to_owned(),
         models::ObjectField {
-            description: field.description.to_owned(),
+            description: describe(&field.description, None, &field.deprecation_reason),
             r#type: typeref_to_ndc_type(&field.r#type),
             arguments: field.arguments.iter().map(map_argument).collect(),
         },
@@ -259,8 +361,16 @@ fn map_argument(
     (
         name.to_owned(),
         models::ArgumentInfo {
-            description: argument.description.to_owned(),
-            argument_type: typeref_to_ndc_type(&argument.r#type),
+            description: describe(
+                &argument.description,
+                argument.default_value.as_ref(),
+                &argument.deprecation_reason,
+            ),
+            argument_type: match (&argument.r#type, &argument.default_value) {
+                // non-null arguments with a default may be omitted, so are advertised as nullable
+                (TypeRef::NonNull(underlying), Some(_)) => typeref_to_ndc_type(underlying),
+                (r#type, _) => typeref_to_ndc_type(r#type),
+            },
         },
     )
 }
@@ -271,13 +381,45 @@ fn map_input_object_field(
     (
         name.to_owned(),
         models::ObjectField {
-            description: field.description.to_owned(),
-            r#type: typeref_to_ndc_type(&field.r#type),
+            description: describe(
+                &field.description,
+                field.default_value.as_ref(),
+                &field.deprecation_reason,
+            ),
+            r#type: match (&field.r#type, &field.default_value) {
+                // non-null fields with a default may be omitted, so are advertised as nullable like arguments
+                (TypeRef::NonNull(underlying), Some(_)) => typeref_to_ndc_type(underlying),
+                (r#type, _) => typeref_to_ndc_type(r#type),
+            },
             arguments: BTreeMap::new(),
         },
     )
 }
 
+/// Append the default value and deprecation reason, if any, to a description
+fn describe(
+    description: &Option<String>,
+    default_value: Option<&Value<'static, String>>,
+    deprecation_reason: &Option<String>,
+) -> Option<String> {
+    let paragraphs: Vec<String> = description
+        .iter()
+        .cloned()
+        .chain(default_value.map(|value| format!("Defaults to {value}")))
+        .chain(
+            deprecation_reason
+                .iter()
+                .map(|reason| format!("Deprecated: {reason}")),
+        )
+        .collect();
+
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
 fn typeref_to_ndc_type(typeref: &TypeRef) -> models::Type {
     match typeref {
         TypeRef::Named(name) => models::Type::Nullable {
@@ -303,21 +445,234 @@ fn typeref_to_ndc_type(typeref: &TypeRef) -> models::Type {
     }
 }
 
-// Guess the type representation by common GraphQL scalar names such as String, Int, Float, Boolean, etc...
-// https://spec.graphql.org/draft/#sec-Scalars.Built-in-Scalars
-fn type_name_to_representation(name: &TypeName) -> TypeRepresentation {
-    match name.to_string().to_lowercase().as_str() {
-        "bool" | "boolean" => TypeRepresentation::Boolean,
-        "int8" | "tinyint" => TypeRepresentation::Int8,
-        "int16" | "smallint" => TypeRepresentation::Int16,
-        "int" | "int32" | "serial" => TypeRepresentation::Int32,
-        "int64" => TypeRepresentation::Int64,
-        "bigint" | "bigserial" => TypeRepresentation::BigInteger,
-        "float8" | "float16" | "float32" => TypeRepresentation::Float32,
-        "float" | "float64" => TypeRepresentation::Float64,
-        "date" => TypeRepresentation::Date,
-        "timestamptz" | "datetime" => TypeRepresentation::TimestampTZ,
-        "id" | "string" | "uuid" | "text" | "citext" | "varchar" => TypeRepresentation::String,
-        _ => TypeRepresentation::JSON,
-    }
+#[test]
+fn test_naming() {
+    use crate::config::{NameMapping, SchemaConfig};
+
+    let schema_document = crate::config::schema::parse_schema(
+        r"
+        type Query {
+          user(id: ID!): User
+          users: [User!]!
+        }
+        type Mutation {
+          createUser(name: String!): User
+        }
+        type User {
+          id: ID!
+          name: String!
+        }
+        ",
+    )
+    .unwrap();
+
+    let naming = NamingConfig {
+        functions: NameMapping {
+            prefix: "crm_".to_string(),
+            suffix: String::new(),
+            rename: BTreeMap::from([("users".to_string(), "crm_allUsers".to_string())]),
+        },
+        procedures: NameMapping {
+            prefix: "crm_".to_string(),
+            suffix: String::new(),
+            rename: BTreeMap::new(),
+        },
+        types: NameMapping {
+            prefix: "Crm".to_string(),
+            suffix: String::new(),
+            rename: BTreeMap::from([("String".to_string(), "String".to_string())]),
+        },
+    };
+
+    assert_eq!(
+        naming.functions.upstream_name("crm_user").as_deref(),
+        Some("user")
+    );
+    assert_eq!(
+        naming.functions.upstream_name("crm_allUsers").as_deref(),
+        Some("users")
+    );
+    assert_eq!(naming.functions.upstream_name("crm_users"), None);
+    assert_eq!(naming.functions.upstream_name("user"), None);
+
+    let schema_config = SchemaConfig {
+        response: ResponseConfig {
+            forward_headers: vec!["x-*".to_string()],
+            ..ResponseConfig::default()
+        },
+        naming,
+        ..SchemaConfig::default()
+    };
+    let schema = SchemaDefinition::new(&schema_document, &schema_config).unwrap();
+
+    let schema_response = schema_response(
+        &schema,
+        &schema_config.request,
+        &schema_config.response,
+        &schema_config.naming,
+    );
+
+    let function_names: Vec<String> = schema_response
+        .functions
+        .iter()
+        .map(|function| function.name.to_string())
+        .collect();
+    assert_eq!(function_names, ["crm_user", "crm_allUsers"]);
+    assert_eq!(
+        schema_response.procedures[0].name.as_str(),
+        "crm_createUser"
+    );
+
+    let scalar_type_names: Vec<String> = schema_response
+        .scalar_types
+        .keys()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(scalar_type_names, ["CrmID", "String", "_HeaderMap"]);
+
+    let object_type_names: Vec<String> = schema_response
+        .object_types
+        .keys()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        object_type_names,
+        [
+            "CrmUser",
+            "_crm_allUsersQueryResponse",
+            "_crm_createUserMutationResponse",
+            "_crm_userQueryResponse"
+        ]
+    );
+
+    let response_type = &schema_response.object_types["_crm_allUsersQueryResponse"];
+    assert_eq!(
+        response_type.fields["response"].r#type,
+        Type::Array {
+            element_type: Box::new(Type::Named {
+                name: "CrmUser".into()
+            })
+        }
+    );
+    assert_eq!(
+        schema_response.object_types["CrmUser"].fields["id"].r#type,
+        Type::Named {
+            name: "CrmID".into()
+        }
+    );
+}
+
+#[test]
+fn test_deprecation_descriptions() {
+    use crate::config::SchemaConfig;
+
+    let schema_document = crate::config::schema::parse_schema(
+        r#"
+        type Query {
+          "Look up a user"
+          user(id: ID, legacyId: Int @deprecated(reason: "Use id")): User
+          userById(id: ID!): User @deprecated
+        }
+        type User {
+          id: ID!
+          "The user's login"
+          login: String @deprecated(reason: "Use email")
+        }
+        "#,
+    )
+    .unwrap();
+
+    let schema_config = SchemaConfig::default();
+    let schema = SchemaDefinition::new(&schema_document, &schema_config).unwrap();
+
+    let schema_response = schema_response(
+        &schema,
+        &schema_config.request,
+        &schema_config.response,
+        &schema_config.naming,
+    );
+
+    let user = &schema_response.functions[0];
+    assert_eq!(user.description.as_deref(), Some("Look up a user"));
+    assert_eq!(
+        user.arguments["legacyId"].description.as_deref(),
+        Some("Deprecated: Use id")
+    );
+    assert_eq!(
+        schema_response.functions[1].description.as_deref(),
+        Some("Deprecated: No longer supported")
+    );
+    assert_eq!(
+        schema_response.object_types["User"].fields["login"]
+            .description
+            .as_deref(),
+        Some("The user's login\n\nDeprecated: Use email")
+    );
+}
+
+#[test]
+fn test_default_values() {
+    use crate::config::SchemaConfig;
+
+    let schema_document = crate::config::schema::parse_schema(
+        r#"
+        type Query {
+          users(limit: Int! = 10, "Sort order" order: String = "asc", offset: Int!, page: Page): [String!]!
+        }
+        input Page {
+          size: Int! = 20
+          after: String!
+        }
+        "#,
+    )
+    .unwrap();
+
+    let schema_config = SchemaConfig::default();
+    let schema = SchemaDefinition::new(&schema_document, &schema_config).unwrap();
+
+    let schema_response = schema_response(
+        &schema,
+        &schema_config.request,
+        &schema_config.response,
+        &schema_config.naming,
+    );
+    let arguments = &schema_response.functions[0].arguments;
+
+    assert_eq!(
+        arguments["limit"].description.as_deref(),
+        Some("Defaults to 10")
+    );
+    assert_eq!(
+        arguments["limit"].argument_type,
+        Type::Nullable {
+            underlying_type: Box::new(Type::Named { name: "Int".into() })
+        }
+    );
+    assert_eq!(
+        arguments["order"].description.as_deref(),
+        Some("Sort order\n\nDefaults to \"asc\"")
+    );
+    assert_eq!(
+        arguments["offset"].argument_type,
+        Type::Named { name: "Int".into() }
+    );
+
+    let page_fields = &schema_response.object_types["Page"].fields;
+
+    assert_eq!(
+        page_fields["size"].description.as_deref(),
+        Some("Defaults to 20")
+    );
+    assert_eq!(
+        page_fields["size"].r#type,
+        Type::Nullable {
+            underlying_type: Box::new(Type::Named { name: "Int".into() })
+        }
+    );
+    assert_eq!(
+        page_fields["after"].r#type,
+        Type::Named {
+            name: "String".into()
+        }
+    );
 }
@@ -0,0 +1,279 @@
+use common::{
+    client::{get_http_client, GraphQLRequest},
+    config::{schema::SchemaDefinition, ConnectionConfig},
+    oauth2::{OAuth2Error, TokenCache},
+};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{collections::BTreeMap, error::Error, fmt::Display};
+
+const TYPENAME_QUERY: &str = "query { __typename }";
+const ROOT_FIELDS_QUERY: &str =
+    "query { __schema { queryType { fields { name } } mutationType { fields { name } } } }";
+
+#[derive(Debug)]
+pub enum EndpointCheckError {
+    Client(String),
+    OAuth2(OAuth2Error),
+    Connection {
+        endpoint: String,
+        message: String,
+    },
+    Tls {
+        endpoint: String,
+        message: String,
+    },
+    Timeout {
+        endpoint: String,
+    },
+    Unauthorized {
+        status: StatusCode,
+        body: String,
+    },
+    Status {
+        status: StatusCode,
+        body: String,
+    },
+    InvalidResponse(String),
+    GraphQLErrors(String),
+    MissingRootFields {
+        root_type: &'static str,
+        fields: Vec<String>,
+    },
+}
+
+impl Error for EndpointCheckError {}
+
+impl Display for EndpointCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EndpointCheckError::Client(message) => write!(f, "Could not create HTTP client: {message}"),
+            EndpointCheckError::OAuth2(err) => write!(f, "{err}"),
+            EndpointCheckError::Connection { endpoint, message } => write!(f, "Could not connect to {endpoint}: {message}"),
+            EndpointCheckError::Tls { endpoint, message } => write!(f, "TLS handshake with {endpoint} failed, check the endpoint uses a valid certificate: {message}"),
+            EndpointCheckError::Timeout { endpoint } => write!(f, "Request to {endpoint} timed out"),
+            EndpointCheckError::Unauthorized { status, body } => write!(f, "Endpoint rejected the configured credentials with status {status}, check the execution headers: {body}"),
+            EndpointCheckError::Status { status, body } => write!(f, "Endpoint responded with status {status}: {body}"),
+            EndpointCheckError::InvalidResponse(message) => write!(f, "Endpoint did not respond with a GraphQL response: {message}"),
+            EndpointCheckError::GraphQLErrors(errors) => write!(f, "Endpoint responded with errors: {errors}"),
+            EndpointCheckError::MissingRootFields { root_type, fields } => write!(f, "{root_type} fields missing from the live schema: {}", fields.join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RootTypesIntrospection {
+    #[serde(rename = "__schema")]
+    schema: RootTypes,
+}
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RootTypes {
+    query_type: Option<RootType>,
+    mutation_type: Option<RootType>,
+}
+#[derive(Debug, Deserialize)]
+struct RootType {
+    fields: Vec<RootTypeField>,
+}
+#[derive(Debug, Deserialize)]
+struct RootTypeField {
+    name: String,
+}
+
+/// Check the endpoint can be reached with the configured headers, and has all configured root fields.
+/// Connection problems end the check, missing root fields are all reported.
+pub async fn check_endpoint(
+    connection: &ConnectionConfig,
+    schema: &SchemaDefinition,
+) -> Result<(), Vec<EndpointCheckError>> {
+    let client = get_http_client(connection)
+        .map_err(|err| vec![EndpointCheckError::Client(err.to_string())])?;
+
+    let mut headers = connection.headers.clone();
+    TokenCache::default()
+        .authorize(&mut headers, &client, connection)
+        .await
+        .map_err(|err| vec![EndpointCheckError::OAuth2(err)])?;
+
+    post_graphql::<serde_json::Value>(&client, &connection.endpoint, &headers, TYPENAME_QUERY)
+        .await
+        .map_err(|err| vec![err])?;
+
+    let live_root_types = post_graphql::<RootTypesIntrospection>(
+        &client,
+        &connection.endpoint,
+        &headers,
+        ROOT_FIELDS_QUERY,
+    )
+    .await
+    .map_err(|err| vec![err])?
+    .schema;
+
+    let errors: Vec<_> = missing_root_fields(schema, &live_root_types).collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn missing_root_fields(
+    schema: &SchemaDefinition,
+    live_root_types: &RootTypes,
+) -> impl Iterator<Item = EndpointCheckError> {
+    let missing = |configured_fields: Vec<&str>, live_type: &Option<RootType>| -> Vec<String> {
+        configured_fields
+            .into_iter()
+            .filter(|field| {
+                !live_type
+                    .iter()
+                    .flat_map(|live_type| &live_type.fields)
+                    .any(|live_field| live_field.name == *field)
+            })
+            .map(ToOwned::to_owned)
+            .collect()
+    };
+
+    let query_fields = missing(
+        schema
+            .query_fields
+            .keys()
+            .map(|name| name.as_str())
+            .collect(),
+        &live_root_types.query_type,
+    );
+    let mutation_fields = missing(
+        schema
+            .mutation_fields
+            .keys()
+            .map(|name| name.as_str())
+            .collect(),
+        &live_root_types.mutation_type,
+    );
+
+    [("Query", query_fields), ("Mutation", mutation_fields)]
+        .into_iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(root_type, fields)| EndpointCheckError::MissingRootFields { root_type, fields })
+}
+
+async fn post_graphql<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    endpoint: &str,
+    headers: &BTreeMap<String, String>,
+    query: &str,
+) -> Result<T, EndpointCheckError> {
+    let mut request = client.post(endpoint);
+    for (header_name, header_value) in headers {
+        request = request.header(header_name, header_value);
+    }
+
+    let response = request
+        .json(&GraphQLRequest::new(query, &BTreeMap::new()))
+        .send()
+        .await
+        .map_err(|err| request_error(endpoint, &err))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                EndpointCheckError::Unauthorized { status, body }
+            }
+            _ => EndpointCheckError::Status { status, body },
+        });
+    }
+
+    let response: graphql_client::Response<T> = response
+        .json()
+        .await
+        .map_err(|err| EndpointCheckError::InvalidResponse(err.to_string()))?;
+
+    if let Some(errors) = response.errors {
+        return Err(EndpointCheckError::GraphQLErrors(
+            serde_json::to_string(&errors).unwrap_or_default(),
+        ));
+    }
+
+    response
+        .data
+        .ok_or_else(|| EndpointCheckError::InvalidResponse("response has no data".to_owned()))
+}
+
+/// reqwest reports TLS problems as connection errors, so tell them apart from failing to open a connection
+fn request_error(endpoint: &str, err: &reqwest::Error) -> EndpointCheckError {
+    if err.is_timeout() {
+        return EndpointCheckError::Timeout {
+            endpoint: endpoint.to_owned(),
+        };
+    }
+
+    let mut messages = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(err) = source {
+        messages.push(err.to_string());
+        source = err.source();
+    }
+    let message = messages.join(": ");
+
+    let lowercase_message = message.to_lowercase();
+    let mentions_tls = ["certificate", "tls", "ssl"]
+        .iter()
+        .any(|term| lowercase_message.contains(term));
+    let failed_handshake = err.is_connect()
+        && endpoint.to_lowercase().starts_with("https:")
+        && !["tcp connect error", "dns error"]
+            .iter()
+            .any(|term| lowercase_message.contains(term));
+
+    if mentions_tls || failed_handshake {
+        EndpointCheckError::Tls {
+            endpoint: endpoint.to_owned(),
+            message,
+        }
+    } else {
+        EndpointCheckError::Connection {
+            endpoint: endpoint.to_owned(),
+            message,
+        }
+    }
+}
+
+#[test]
+fn test_missing_root_fields() {
+    use common::config::{schema::parse_schema, SchemaConfig};
+
+    let schema = SchemaDefinition::new(
+        &parse_schema(
+            r"
+            type Query { user: String users: [String] }
+            type Mutation { createUser: String }
+            ",
+        )
+        .unwrap(),
+        &SchemaConfig::default(),
+    )
+    .unwrap();
+
+    let live_root_types: RootTypesIntrospection = serde_json::from_value(serde_json::json!({
+        "__schema": {
+            "queryType": { "fields": [{ "name": "user" }, { "name": "search" }] },
+            "mutationType": null,
+        }
+    }))
+    .unwrap();
+
+    let errors: Vec<String> = missing_root_fields(&schema, &live_root_types.schema)
+        .map(|err| err.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "Query fields missing from the live schema: users",
+            "Mutation fields missing from the live schema: createUser",
+        ]
+    );
+}
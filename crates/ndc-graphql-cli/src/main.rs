@@ -1,29 +1,46 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use common::{
     capabilities::capabilities_response,
     config::{
         config_file::{
-            ConfigValue, ServerConfigFile, CONFIG_FILE_NAME, CONFIG_SCHEMA_FILE_NAME,
-            SCHEMA_FILE_NAME,
+            ConfigValue, ConnectionConfigFile, RequestConfigFile, ResponseConfigFile,
+            ServerConfigFile, CONFIG_FILE_NAME, CONFIG_SCHEMA_FILE_NAME, SCHEMA_FILE_NAME,
         },
-        schema::SchemaDefinition,
-        ConnectionConfig,
+        schema::{default_scalar_representations, parse_schema, SchemaDefinition},
+        ConnectionConfig, OAuth2Config, SchemaConfig,
     },
+    graphql::{execute_graphql_introspection, schema_from_introspection},
+    schema_diff::{diff_schemas, SchemaChange},
     schema_response::schema_response,
 };
-use graphql::{execute_graphql_introspection, schema_from_introspection};
+use endpoint_check::check_endpoint;
 use graphql_parser::schema;
-use ndc_graphql_cli::graphql;
+use metadata::ddn_metadata;
+use ndc_graphql::{
+    connector::{setup::GraphQLConnectorSetup, GraphQLConnector},
+    query_builder::{build_mutation_document, build_query_document},
+};
 use ndc_models as models;
+use ndc_sdk::{
+    connector::{Connector, ConnectorSetup},
+    json_response::JsonResponse,
+};
+use operation::{ndc_request, NdcRequest};
 use schemars::schema_for;
 use serde::Serialize;
 use std::{
+    collections::{BTreeMap, HashMap},
     env,
     error::Error,
+    iter,
     path::{Path, PathBuf},
 };
 use tokio::fs;
 
+mod endpoint_check;
+mod metadata;
+mod operation;
+
 #[derive(Parser)]
 struct CliArgs {
     /// The PAT token which can be used to make authenticated calls to Hasura Cloud
@@ -67,14 +84,95 @@ struct CliArgs {
 
 #[derive(Clone, Subcommand)]
 enum Command {
-    Init {},
-    Update {},
-    Validate {},
+    Init(Box<InitArgs>),
+    /// Introspect the endpoint, report differences with the current schema.graphql, and update the configuration
+    Update {
+        /// Report differences without changing any files
+        #[arg(long)]
+        dry_run: bool,
+        /// Fail without changing any files if the live schema has breaking changes
+        #[arg(long)]
+        fail_on_breaking: bool,
+    },
+    Validate {
+        /// Also resolve all configured values, and check the execution endpoint responds and has all configured root fields
+        #[arg(long)]
+        check_endpoint: bool,
+    },
+    /// Compare schema.graphql with the live schema of the introspection endpoint, without changing any files
+    Diff {},
     Watch {},
     PrintSchemaAndCapabilities {},
+    /// Translate a GraphQL operation against the NDC schema of the connector into an NDC request,
+    /// and print it with the GraphQL document sent upstream
+    Translate {
+        /// GraphQL operation selecting a function, or procedures, eg. "{ users(limit: 10) { id name } }"
+        #[arg(required_unless_present = "file")]
+        operation: Option<String>,
+        /// Read the GraphQL operation from a file instead
+        #[arg(long, value_name = "PATH", conflicts_with = "operation")]
+        file: Option<PathBuf>,
+        /// Values for the variables of the operation, as a JSON object
+        #[arg(long, value_name = "JSON")]
+        variables: Option<String>,
+        /// Execute the request against the configured execution endpoint, and print the NDC response
+        #[arg(long)]
+        execute: bool,
+        /// Write the NDC request to a file, eg. as a test fixture
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Print Hasura DDN metadata for the commands and types of the connector, including header forwarding settings
+    PrintMetadata {
+        /// Name of the DataConnectorLink for this connector
+        #[arg(long, default_value = "graphql")]
+        data_connector_name: String,
+    },
     UpgradeConfiguration {},
 }
 
+/// Flags for a scriptable init, any connection settings not given default to the GRAPHQL_ENDPOINT env var and no headers
+#[derive(Clone, Args)]
+struct InitArgs {
+    /// Endpoint URL of the target GraphQL API
+    #[arg(long, value_name = "URL", conflicts_with = "endpoint_env")]
+    endpoint: Option<String>,
+    /// Environment variable to read the endpoint URL from at runtime
+    #[arg(long, value_name = "VAR")]
+    endpoint_env: Option<String>,
+    /// Static header to send with each request, may be repeated
+    #[arg(long = "header", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    headers: Vec<(String, String)>,
+    /// Static header to send with each request, read from an environment variable at runtime, may be repeated
+    #[arg(long = "header-env", value_name = "NAME=VAR", value_parser = parse_key_value)]
+    header_envs: Vec<(String, String)>,
+    /// Request header to forward to the target API, supports glob patterns eg. "X-Hasura-*", may be repeated
+    #[arg(long = "forward-request-header", value_name = "PATTERN")]
+    forward_request_headers: Vec<String>,
+    /// Response header to forward from the target API, supports glob patterns, may be repeated
+    #[arg(long = "forward-response-header", value_name = "PATTERN")]
+    forward_response_headers: Vec<String>,
+    /// Endpoint URL to introspect, if different from the execution endpoint
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with = "introspection_endpoint_env"
+    )]
+    introspection_endpoint: Option<String>,
+    /// Environment variable to read the introspection endpoint URL from, if different from the execution endpoint
+    #[arg(long, value_name = "VAR")]
+    introspection_endpoint_env: Option<String>,
+    /// Static header for introspection only, may be repeated. If any introspection headers are given, execution headers are not used for introspection
+    #[arg(long = "introspection-header", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    introspection_headers: Vec<(String, String)>,
+    /// Static header for introspection only, read from an environment variable, may be repeated
+    #[arg(long = "introspection-header-env", value_name = "NAME=VAR", value_parser = parse_key_value)]
+    introspection_header_envs: Vec<(String, String)>,
+    /// Introspect the endpoint right away, writing schema.graphql
+    #[arg(long)]
+    introspect: bool,
+}
+
 #[derive(Clone, ValueEnum)]
 enum LogLevel {
     Panic,
@@ -102,17 +200,29 @@ async fn main() -> Result<(), Box<dyn Error>> {
     };
 
     match args.command {
-        Command::Init {} => {
+        Command::Init(init_args) => {
             write_config_schema_file(&context_path).await?;
-            write_config_file(&context_path, &ServerConfigFile::default()).await?;
-            println!("Configuration Initialized. Add your endpoint, then introspect your schema to continue.")
+            write_config_file(&context_path, &init_config_file(&init_args)).await?;
+
+            if init_args.introspect {
+                let (config_file, schema_document) =
+                    update_config(&context_path, false, false).await?;
+
+                validate_config(config_file, schema_document).await?;
+            } else {
+                println!("Configuration Initialized. Add your endpoint, then introspect your schema to continue.")
+            }
         }
-        Command::Update {} => {
-            let (config_file, schema_document) = update_config(&context_path).await?;
+        Command::Update {
+            dry_run,
+            fail_on_breaking,
+        } => {
+            let (config_file, schema_document) =
+                update_config(&context_path, dry_run, fail_on_breaking).await?;
 
             validate_config(config_file, schema_document).await?;
         }
-        Command::Validate {} => {
+        Command::Validate { check_endpoint } => {
             let config_file = read_config_file(&context_path)
                 .await?
                 .ok_or_else(|| format!("Could not find {CONFIG_FILE_NAME}"))?;
@@ -120,7 +230,31 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .await?
                 .ok_or_else(|| format!("Could not find {SCHEMA_FILE_NAME}"))?;
 
-            validate_config(config_file, schema_document).await?;
+            validate_config(config_file.clone(), schema_document.clone()).await?;
+
+            if check_endpoint {
+                check_execution_endpoint(&context_path, &config_file, &schema_document).await?;
+            }
+        }
+        Command::Diff {} => {
+            let config_file = read_config_file(&context_path)
+                .await?
+                .ok_or_else(|| format!("Could not find {CONFIG_FILE_NAME}"))?;
+            let schema_document = read_schema_file(&context_path)
+                .await?
+                .ok_or_else(|| format!("Could not find {SCHEMA_FILE_NAME}"))?;
+
+            let live_schema_document =
+                introspect_schema(&context_path, &config_file.introspection).await?;
+
+            let mut schema_config = SchemaConfig::from(&config_file);
+            let configured_schema = SchemaDefinition::new(&schema_document, &schema_config)
+                .map_err(|errors| format!("Invalid {SCHEMA_FILE_NAME}:\n{errors}"))?;
+            schema_config.retain_defined_scalars(&live_schema_document);
+            let live_schema = SchemaDefinition::new(&live_schema_document, &schema_config)
+                .map_err(|errors| format!("Invalid live schema:\n{errors}"))?;
+
+            print_schema_changes(&diff_schemas(&configured_schema, &live_schema));
         }
         Command::Watch {} => {
             todo!("implement watch command")
@@ -133,14 +267,16 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .await?
                 .ok_or_else(|| format!("Could not find {SCHEMA_FILE_NAME}"))?;
 
-            let request_config = config_file.request.unwrap_or_default().into();
-            let response_config = config_file.response.unwrap_or_default().into();
-
-            let schema =
-                SchemaDefinition::new(&schema_document, &request_config, &response_config)?;
+            let schema_config = SchemaConfig::from(&config_file);
+            let schema = SchemaDefinition::new(&schema_document, &schema_config)?;
 
             let schema_and_capabilities = SchemaAndCapabilities {
-                schema: schema_response(&schema, &request_config, &response_config),
+                schema: schema_response(
+                    &schema,
+                    &schema_config.request,
+                    &schema_config.response,
+                    &schema_config.naming,
+                ),
                 capabilities: capabilities_response(),
             };
 
@@ -150,6 +286,54 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     .expect("Schema and capabilities should serialize to JSON")
             )
         }
+        Command::PrintMetadata {
+            data_connector_name,
+        } => {
+            let config_file = read_config_file(&context_path)
+                .await?
+                .ok_or_else(|| format!("Could not find {CONFIG_FILE_NAME}"))?;
+            let schema_document = read_schema_file(&context_path)
+                .await?
+                .ok_or_else(|| format!("Could not find {SCHEMA_FILE_NAME}"))?;
+
+            let schema_config = SchemaConfig::from(&config_file);
+            let schema = SchemaDefinition::new(&schema_document, &schema_config)?;
+            let schema = schema_response(
+                &schema,
+                &schema_config.request,
+                &schema_config.response,
+                &schema_config.naming,
+            );
+
+            for document in ddn_metadata(
+                &schema,
+                &schema_config.request,
+                &schema_config.response,
+                &data_connector_name,
+            ) {
+                println!("{document}");
+            }
+        }
+        Command::Translate {
+            operation,
+            file,
+            variables,
+            execute,
+            output,
+        } => {
+            let operation = match (operation, file) {
+                (Some(operation), _) => operation,
+                (None, Some(file)) => fs::read_to_string(file).await?,
+                (None, None) => unreachable!("clap requires an operation or file"),
+            };
+            let variables: serde_json::Map<String, serde_json::Value> = variables
+                .map(|variables| serde_json::from_str(&variables))
+                .transpose()
+                .map_err(|err| format!("Variables should be a JSON object: {err}"))?
+                .unwrap_or_default();
+
+            translate_operation(&context_path, &operation, &variables, execute, output).await?;
+        }
         Command::UpgradeConfiguration {} => {
             println!("Upgrade Configuration command is currently a NOOP")
         }
@@ -158,6 +342,201 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+async fn translate_operation(
+    context_path: &Path,
+    operation: &str,
+    variables: &serde_json::Map<String, serde_json::Value>,
+    execute: bool,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let mut environment: HashMap<String, String> = env::vars().collect();
+
+    // the execution connection settings are only needed to execute the request
+    if !execute {
+        let config_file = read_config_file(context_path)
+            .await?
+            .ok_or_else(|| format!("Could not find {CONFIG_FILE_NAME}"))?;
+        let execution = &config_file.execution;
+        let oauth2_values = execution
+            .oauth2
+            .iter()
+            .flat_map(|oauth2| oauth2.values().map(|(_, value)| value));
+        for value in iter::once(&execution.endpoint)
+            .chain(execution.headers.values())
+            .chain(oauth2_values)
+        {
+            for name in value.environment_variables()? {
+                environment.entry(name.to_owned()).or_default();
+            }
+        }
+    }
+
+    let setup = GraphQLConnectorSetup::new(environment);
+    let configuration = setup.read_configuration(context_path).await?;
+
+    let schema = schema_response(
+        &configuration.schema,
+        &configuration.request,
+        &configuration.response,
+        &configuration.naming,
+    );
+
+    let (request, schema_file_name) =
+        match ndc_request(&schema, operation, variables).map_err(|err| err.to_string())? {
+            NdcRequest::Query(request) => (request, "_query_request.schema.json"),
+            NdcRequest::Mutation(request) => (request, "_mutation_request.schema.json"),
+        };
+    println!(
+        "NDC request:\n{}\n",
+        serde_json::to_string_pretty(&request)?
+    );
+
+    if let Some(output) = output {
+        let mut fixture =
+            serde_json::Map::from_iter([("$schema".to_owned(), schema_file_name.into())]);
+        fixture.extend(request.as_object().cloned().unwrap_or_default());
+        fs::write(&output, serde_json::to_string_pretty(&fixture)?).await?;
+        println!("Wrote NDC request to {}\n", output.display());
+    }
+
+    let state = if execute {
+        Some(
+            setup
+                .try_init_state(&configuration, &mut prometheus::Registry::new())
+                .await
+                .map_err(|err| err.to_string())?,
+        )
+    } else {
+        None
+    };
+
+    let response = match request.get("collection") {
+        Some(_) => {
+            let request: models::QueryRequest = serde_json::from_value(request)?;
+            let operation =
+                build_query_document(&request, &configuration).map_err(|err| err.to_string())?;
+            println!("Upstream query:\n{}\n", operation.query);
+            println!(
+                "Upstream variables:\n{}\n",
+                serde_json::to_string_pretty(&operation.variables)?
+            );
+
+            match &state {
+                Some(state) => Some(json_response(
+                    GraphQLConnector::query(&configuration, state, request)
+                        .await
+                        .map_err(|err| err.to_string())?,
+                )?),
+                None => None,
+            }
+        }
+        None => {
+            let request: models::MutationRequest = serde_json::from_value(request)?;
+            let operation =
+                build_mutation_document(&request, &configuration).map_err(|err| err.to_string())?;
+            println!("Upstream mutation:\n{}\n", operation.query);
+            println!(
+                "Upstream variables:\n{}\n",
+                serde_json::to_string_pretty(&operation.variables)?
+            );
+
+            match &state {
+                Some(state) => Some(json_response(
+                    GraphQLConnector::mutation(&configuration, state, request)
+                        .await
+                        .map_err(|err| err.to_string())?,
+                )?),
+                None => None,
+            }
+        }
+    };
+
+    if let Some(response) = response {
+        println!("NDC response:\n{response}");
+    }
+
+    Ok(())
+}
+
+fn json_response<A: Serialize>(response: JsonResponse<A>) -> Result<String, Box<dyn Error>> {
+    Ok(match response {
+        JsonResponse::Value(value) => serde_json::to_string_pretty(&value)?,
+        JsonResponse::Serialized(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
+
+fn init_config_file(args: &InitArgs) -> ServerConfigFile {
+    let connection = |endpoint: &Option<String>,
+                      endpoint_env: &Option<String>,
+                      headers: &[(String, String)],
+                      header_envs: &[(String, String)]| {
+        let endpoint = match (endpoint, endpoint_env) {
+            (Some(endpoint), _) => Some(ConfigValue::Value(endpoint.to_owned())),
+            (None, Some(endpoint_env)) => Some(ConfigValue::ValueFromEnv(endpoint_env.to_owned())),
+            (None, None) => None,
+        };
+        let headers: BTreeMap<_, _> =
+            headers
+                .iter()
+                .map(|(name, value)| (name.to_owned(), ConfigValue::Value(value.to_owned())))
+                .chain(header_envs.iter().map(|(name, env)| {
+                    (name.to_owned(), ConfigValue::ValueFromEnv(env.to_owned()))
+                }))
+                .collect();
+        (endpoint, headers)
+    };
+
+    let (endpoint, headers) = connection(
+        &args.endpoint,
+        &args.endpoint_env,
+        &args.headers,
+        &args.header_envs,
+    );
+    let execution = ConnectionConfigFile {
+        endpoint: endpoint.unwrap_or_else(|| ConnectionConfigFile::default().endpoint),
+        headers,
+        oauth2: None,
+    };
+
+    // introspection uses the execution settings, unless overridden
+    let (introspection_endpoint, introspection_headers) = connection(
+        &args.introspection_endpoint,
+        &args.introspection_endpoint_env,
+        &args.introspection_headers,
+        &args.introspection_header_envs,
+    );
+    let introspection = ConnectionConfigFile {
+        endpoint: introspection_endpoint.unwrap_or_else(|| execution.endpoint.clone()),
+        headers: if introspection_headers.is_empty() {
+            execution.headers.clone()
+        } else {
+            introspection_headers
+        },
+        oauth2: None,
+    };
+
+    ServerConfigFile {
+        introspection,
+        execution,
+        request: (!args.forward_request_headers.is_empty()).then(|| RequestConfigFile {
+            forward_headers: Some(args.forward_request_headers.clone()),
+            ..RequestConfigFile::default()
+        }),
+        response: (!args.forward_response_headers.is_empty()).then(|| ResponseConfigFile {
+            forward_headers: Some(args.forward_response_headers.clone()),
+            ..ResponseConfigFile::default()
+        }),
+        ..ServerConfigFile::default()
+    }
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got {value}"))
+}
+
 async fn write_config_file(
     context_path: &Path,
     config: &ServerConfigFile,
@@ -204,7 +583,7 @@ async fn read_schema_file(
 ) -> Result<Option<schema::Document<'static, String>>, Box<dyn std::error::Error>> {
     let file_path = context_path.join(SCHEMA_FILE_NAME);
     let config: Option<schema::Document<'static, String>> = match fs::read_to_string(file_path).await {
-        Ok(file) => Some(graphql_parser::parse_schema(&file).map(|document| document.into_static())
+        Ok(file) => Some(parse_schema(&file)
             .map_err(|err| format!("Error parsing {SCHEMA_FILE_NAME}: {err}\n\nDelete {SCHEMA_FILE_NAME} to create a fresh file"))),
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
         Err(_) => Some(Err(format!("Error reading {SCHEMA_FILE_NAME}"))),
@@ -217,16 +596,32 @@ async fn validate_config(
     config_file: ServerConfigFile,
     schema_document: graphql_parser::schema::Document<'_, String>,
 ) -> Result<(), Box<dyn Error>> {
-    let request_config = config_file.request.unwrap_or_default().into();
-    let response_config = config_file.response.unwrap_or_default().into();
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::from(&config_file))
+        .map_err(|errors| {
+            for error in &errors.0 {
+                match error.error.config_node_path() {
+                    Some(node_path) => eprintln!(
+                        "{error}\n  (configure in {CONFIG_FILE_NAME} at {})",
+                        node_path.join(".")
+                    ),
+                    None => eprintln!("{error}"),
+                }
+            }
+            format!("Found {} problem(s) with the configuration", errors.0.len())
+        })?;
 
-    let _schema = SchemaDefinition::new(&schema_document, &request_config, &response_config)?;
+    println!("{}", schema.describe_root_types());
+    if let Some(unsupported_types) = schema.describe_unsupported_types() {
+        println!("{unsupported_types}");
+    }
 
     Ok(())
 }
 
 async fn update_config(
     context_path: &Path,
+    dry_run: bool,
+    fail_on_breaking: bool,
 ) -> Result<
     (
         ServerConfigFile,
@@ -245,30 +640,99 @@ async fn update_config(
     }?;
 
     // CLI uses the introspection connection
-    let connection_file = &config_file.introspection;
+    let schema_document = introspect_schema(context_path, &config_file.introspection).await?;
 
-    let connection = ConnectionConfig {
-        endpoint: read_config_value(&connection_file.endpoint)?,
-        headers: connection_file
-            .headers
-            .iter()
-            .map(|(header_name, header_value)| {
-                Ok((header_name.to_owned(), read_config_value(header_value)?))
-            })
-            .collect::<Result<_, std::env::VarError>>()?,
+    update_schema(
+        context_path,
+        config_file,
+        schema_document,
+        dry_run,
+        fail_on_breaking,
+    )
+    .await
+}
+
+/// Report differences between the current schema file and the introspected schema,
+/// then write the introspected schema and the representations of its scalars
+async fn update_schema(
+    context_path: &Path,
+    mut config_file: ServerConfigFile,
+    schema_document: graphql_parser::schema::Document<'static, String>,
+    dry_run: bool,
+    fail_on_breaking: bool,
+) -> Result<
+    (
+        ServerConfigFile,
+        graphql_parser::schema::Document<'static, String>,
+    ),
+    Box<dyn Error>,
+> {
+    // there is nothing to compare to on first update
+    let current_schema = match read_schema_file(context_path).await {
+        Ok(None) => Ok(None),
+        Ok(Some(current_document)) => {
+            SchemaDefinition::new(&current_document, &SchemaConfig::from(&config_file))
+                .map(Some)
+                .map_err(|errors| format!("Invalid {SCHEMA_FILE_NAME}:\n{errors}"))
+        }
+        Err(err) => Err(err.to_string()),
+    };
+    let current_schema = match current_schema {
+        Ok(current_schema) => current_schema,
+        Err(err) if fail_on_breaking => {
+            return Err(format!(
+                "{err}\n\nCould not check the live schema for breaking changes, {SCHEMA_FILE_NAME} was not updated"
+            )
+            .into())
+        }
+        Err(err) => {
+            eprintln!("{err}\n\nCould not compare the live schema with {SCHEMA_FILE_NAME}");
+            None
+        }
     };
 
-    let response = execute_graphql_introspection(&connection).await?;
+    // keep configured representations, infer representations for new scalars,
+    // and drop representations for scalars no longer in the schema,
+    // so the live schema is validated and compared with the representations that will be written
+    let scalars: BTreeMap<_, _> = default_scalar_representations(&schema_document)
+        .into_iter()
+        .map(|(name, inferred_representation)| {
+            let representation = config_file
+                .scalars
+                .get(&name)
+                .cloned()
+                .unwrap_or(inferred_representation);
+            (name, representation)
+        })
+        .collect();
 
-    if let Some(errors) = response.errors {
-        return Err(format!("Introspection error: {}", serde_json::to_string(&errors)?).into());
+    let scalars_changed = scalars != config_file.scalars;
+    config_file.scalars = scalars;
+
+    if let Some(current_schema) = current_schema {
+        let live_schema =
+            SchemaDefinition::new(&schema_document, &SchemaConfig::from(&config_file))
+                .map_err(|errors| format!("Invalid live schema:\n{errors}"))?;
+        let changes = diff_schemas(&current_schema, &live_schema);
+        print_schema_changes(&changes);
+
+        let breaking_changes = changes.iter().filter(|change| change.breaking).count();
+        if fail_on_breaking && breaking_changes > 0 {
+            return Err(format!(
+                "Found {breaking_changes} breaking change(s), {SCHEMA_FILE_NAME} was not updated"
+            )
+            .into());
+        }
     }
 
-    let introspection = response
-        .data
-        .expect("Introspection without error should have data");
+    if dry_run {
+        println!("Dry run, no files were changed");
+        return Ok((config_file, schema_document));
+    }
 
-    let schema_document = schema_from_introspection(introspection);
+    if scalars_changed {
+        write_config_file(context_path, &config_file).await?;
+    }
 
     write_schema_file(context_path, &schema_document).await?;
     write_config_schema_file(context_path).await?;
@@ -276,17 +740,303 @@ async fn update_config(
     Ok((config_file, schema_document))
 }
 
-fn read_config_value(value: &ConfigValue) -> Result<String, std::env::VarError> {
-    match value {
-        ConfigValue::Value(v) => Ok(v.to_owned()),
-        ConfigValue::ValueFromEnv(e) => Ok(env::var(e)?),
+async fn introspect_schema(
+    context_path: &Path,
+    connection_file: &ConnectionConfigFile,
+) -> Result<graphql_parser::schema::Document<'static, String>, Box<dyn Error>> {
+    let connection = resolve_connection(context_path, "introspection", connection_file)
+        .map_err(|errors| errors.join("\n"))?;
+
+    let response = execute_graphql_introspection(&connection).await?;
+
+    if let Some(errors) = response.errors {
+        return Err(format!("Introspection error: {}", serde_json::to_string(&errors)?).into());
+    }
+
+    let introspection = response.data.ok_or("Introspection response has no data")?;
+
+    Ok(schema_from_introspection(introspection)?)
+}
+
+fn print_schema_changes(changes: &[SchemaChange]) {
+    if changes.is_empty() {
+        println!("No differences between {SCHEMA_FILE_NAME} and the live schema");
+        return;
+    }
+
+    for change in changes {
+        let severity = if change.breaking { "breaking" } else { "safe" };
+        println!("[{severity}] {change}");
+    }
+
+    let breaking_changes = changes.iter().filter(|change| change.breaking).count();
+    println!(
+        "Found {breaking_changes} breaking and {} non-breaking difference(s) between {SCHEMA_FILE_NAME} and the live schema",
+        changes.len() - breaking_changes
+    );
+}
+
+async fn check_execution_endpoint(
+    context_path: &Path,
+    config_file: &ServerConfigFile,
+    schema_document: &graphql_parser::schema::Document<'_, String>,
+) -> Result<(), Box<dyn Error>> {
+    let execution = resolve_connection(context_path, "execution", &config_file.execution);
+    let introspection =
+        resolve_connection(context_path, "introspection", &config_file.introspection);
+
+    let execution = match (execution, introspection) {
+        (Ok(execution), Ok(_)) => execution,
+        (execution, introspection) => {
+            let errors: Vec<_> = execution
+                .err()
+                .into_iter()
+                .chain(introspection.err())
+                .flatten()
+                .collect();
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            return Err(format!(
+                "Could not resolve {} value(s) in {CONFIG_FILE_NAME}",
+                errors.len()
+            )
+            .into());
+        }
+    };
+
+    let schema = SchemaDefinition::new(schema_document, &SchemaConfig::from(config_file))
+        .map_err(|errors| format!("Invalid {SCHEMA_FILE_NAME}:\n{errors}"))?;
+
+    check_endpoint(&execution, &schema)
+        .await
+        .map_err(|errors| {
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            format!(
+                "Endpoint check failed for {} with {} problem(s)",
+                execution.endpoint,
+                errors.len()
+            )
+        })?;
+
+    println!(
+        "Endpoint {} responded and has all configured root fields",
+        execution.endpoint
+    );
+
+    Ok(())
+}
+
+/// Resolve all values of a connection, reporting every value that could not be resolved
+fn resolve_connection(
+    context_path: &Path,
+    connection_name: &str,
+    connection_file: &ConnectionConfigFile,
+) -> Result<ConnectionConfig, Vec<String>> {
+    let environment = env::vars().collect();
+    let mut errors = vec![];
+    let mut resolve =
+        |path: String, value: &ConfigValue| match value.resolve(&environment, context_path) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                errors.push(format!("{connection_name}.{path}.{}: {err}", value.key()));
+                None
+            }
+        };
+
+    let endpoint = resolve("endpoint".to_owned(), &connection_file.endpoint);
+    let headers = connection_file
+        .headers
+        .iter()
+        .filter_map(|(header_name, header_value)| {
+            resolve(format!("headers.{header_name}"), header_value)
+                .map(|header_value| (header_name.to_owned(), header_value))
+        })
+        .collect();
+    let oauth2 = match &connection_file.oauth2 {
+        Some(oauth2) => {
+            let [token_url, client_id, client_secret] = oauth2
+                .values()
+                .map(|(key, value)| resolve(format!("oauth2.{key}"), value));
+            token_url.zip(client_id).zip(client_secret).map(
+                |((token_url, client_id), client_secret)| OAuth2Config {
+                    token_url,
+                    client_id,
+                    client_secret,
+                    scopes: oauth2.scopes.clone().unwrap_or_default(),
+                    audience: oauth2.audience.clone(),
+                },
+            )
+        }
+        None => None,
+    };
+
+    match endpoint {
+        Some(endpoint) if errors.is_empty() => Ok(ConnectionConfig {
+            endpoint,
+            headers,
+            oauth2,
+        }),
+        _ => Err(errors),
     }
 }
 
 #[tokio::test]
 #[ignore]
 async fn update_configuration_directory() {
-    update_config(std::path::Path::new("../../config"))
+    update_config(std::path::Path::new("../../config"), false, false)
         .await
         .expect("updating config should work");
 }
+
+#[test]
+fn test_update_args() {
+    let args = CliArgs::parse_from([
+        "ndc-graphql-cli",
+        "update",
+        "--dry-run",
+        "--fail-on-breaking",
+    ]);
+    assert!(matches!(
+        args.command,
+        Command::Update {
+            dry_run: true,
+            fail_on_breaking: true
+        }
+    ));
+
+    let args = CliArgs::parse_from(["ndc-graphql-cli", "update"]);
+    assert!(matches!(
+        args.command,
+        Command::Update {
+            dry_run: false,
+            fail_on_breaking: false
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_update_schema() {
+    use common::config::config_file::ScalarRepresentation;
+
+    let context_path = std::env::temp_dir().join(format!(
+        "ndc-graphql-cli-update-test-{}",
+        std::process::id()
+    ));
+    let schema_path = context_path.join(SCHEMA_FILE_NAME);
+    fs::create_dir_all(&context_path).await.unwrap();
+
+    let current_schema = "type Query { user(id: ID!): String users: [String] }";
+    let live_schema = parse_schema("type Query { user(id: ID!): String }").unwrap();
+    let update = |dry_run: bool, fail_on_breaking: bool| {
+        update_schema(
+            &context_path,
+            ServerConfigFile::default(),
+            live_schema.clone(),
+            dry_run,
+            fail_on_breaking,
+        )
+    };
+
+    // removing a field is breaking
+    fs::write(&schema_path, current_schema).await.unwrap();
+    let err = update(false, true).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Found 1 breaking change(s), {SCHEMA_FILE_NAME} was not updated")
+    );
+    assert_eq!(
+        fs::read_to_string(&schema_path).await.unwrap(),
+        current_schema
+    );
+
+    update(true, false).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(&schema_path).await.unwrap(),
+        current_schema
+    );
+
+    // a current schema that cannot be compared fails the update only when checking for breaking changes
+    fs::write(&schema_path, "type Query {").await.unwrap();
+    let err = update(false, true).await.unwrap_err();
+    assert!(
+        err.to_string().starts_with("Error parsing schema.graphql"),
+        "{err}"
+    );
+    assert_eq!(
+        fs::read_to_string(&schema_path).await.unwrap(),
+        "type Query {"
+    );
+
+    update(false, false).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(&schema_path).await.unwrap(),
+        live_schema.to_string()
+    );
+
+    // representations of scalars removed upstream are dropped, not reported as invalid configuration
+    fs::write(&schema_path, "type Query { balance: Money } scalar Money")
+        .await
+        .unwrap();
+    let config_file = ServerConfigFile {
+        scalars: BTreeMap::from([("Money".into(), ScalarRepresentation::BigDecimal)]),
+        ..ServerConfigFile::default()
+    };
+    let (config_file, _) =
+        update_schema(&context_path, config_file, live_schema.clone(), true, false)
+            .await
+            .unwrap();
+    assert!(config_file.scalars.is_empty());
+
+    // a missing schema file is the first update
+    fs::remove_file(&schema_path).await.unwrap();
+    update(false, true).await.unwrap();
+    assert!(fs::try_exists(&schema_path).await.unwrap());
+
+    fs::remove_dir_all(&context_path).await.unwrap();
+}
+
+#[test]
+fn test_init_config_file() {
+    let args = CliArgs::parse_from([
+        "ndc-graphql-cli",
+        "init",
+        "--endpoint",
+        "https://example.com/graphql",
+        "--header-env",
+        "Authorization=API_TOKEN",
+        "--header",
+        "X-Client=connector",
+        "--forward-request-header",
+        "X-Hasura-*",
+        "--introspection-endpoint-env",
+        "INTROSPECTION_ENDPOINT",
+    ]);
+    let Command::Init(init_args) = args.command else {
+        panic!("expected init command");
+    };
+
+    assert_eq!(
+        serde_json::to_value(init_config_file(&init_args)).unwrap(),
+        serde_json::json!({
+            "$schema": "configuration.schema.json",
+            "introspection": {
+                "endpoint": { "valueFromEnv": "INTROSPECTION_ENDPOINT" },
+                "headers": {
+                    "Authorization": { "valueFromEnv": "API_TOKEN" },
+                    "X-Client": { "value": "connector" },
+                },
+            },
+            "execution": {
+                "endpoint": { "value": "https://example.com/graphql" },
+                "headers": {
+                    "Authorization": { "valueFromEnv": "API_TOKEN" },
+                    "X-Client": { "value": "connector" },
+                },
+            },
+            "request": { "forwardHeaders": ["X-Hasura-*"] },
+        })
+    );
+}
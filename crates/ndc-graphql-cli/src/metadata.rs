@@ -0,0 +1,510 @@
+use common::config::{RequestConfig, ResponseConfig};
+use ndc_models::{self as models, ArgumentInfo, ArgumentName, SchemaResponse};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Display};
+
+/// GraphQL scalars DDN provides, which connector scalars of the same name are represented as
+const DDN_BUILT_IN_SCALARS: [&str; 4] = ["String", "Int", "Float", "Boolean"];
+/// Role given access to the generated types and commands, as in metadata generated by the DDN CLI
+const ADMIN_ROLE: &str = "admin";
+
+/// A DDN metadata object, with comments explaining how to use it
+#[derive(Debug, Clone)]
+pub struct MetadataDocument {
+    pub comments: Vec<String>,
+    pub value: Value,
+}
+
+/// Documents are written as JSON, which is valid YAML, so the output can be used as an `.hml` file
+impl Display for MetadataDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "---")?;
+        for comment in &self.comments {
+            writeln!(f, "# {comment}")?;
+        }
+        write!(
+            f,
+            "{}",
+            serde_json::to_string_pretty(&self.value).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// Generate DDN metadata for the commands and types of the NDC schema,
+/// including the argument presets and response header settings needed for header forwarding
+pub fn ddn_metadata(
+    schema: &SchemaResponse,
+    request: &RequestConfig,
+    response: &ResponseConfig,
+    data_connector_name: &str,
+) -> Vec<MetadataDocument> {
+    let mut documents = vec![];
+
+    if let Some(document) = data_connector_link(request, response, data_connector_name) {
+        documents.push(document);
+    }
+
+    // when forwarding response headers, commands return the response field of generated response types
+    let response_types: BTreeMap<&str, &models::Type> = if response.forward_headers.is_empty() {
+        BTreeMap::new()
+    } else {
+        schema
+            .functions
+            .iter()
+            .map(|function| &function.result_type)
+            .chain(
+                schema
+                    .procedures
+                    .iter()
+                    .map(|procedure| &procedure.result_type),
+            )
+            .filter_map(|result_type| match result_type {
+                models::Type::Named { name } => {
+                    let response_field = schema
+                        .object_types
+                        .get(name.as_str())?
+                        .fields
+                        .get(&response.response_field)?;
+                    Some((name.as_str(), &response_field.r#type))
+                }
+                _ => None,
+            })
+            .collect()
+    };
+    let output_type = |result_type: &models::Type| match result_type {
+        models::Type::Named { name } => response_types
+            .get(name.as_str())
+            .map_or_else(|| ddn_type(result_type), |r#type| ddn_type(r#type)),
+        _ => ddn_type(result_type),
+    };
+
+    for name in schema.scalar_types.keys() {
+        if *name != request.headers_type_name {
+            documents.extend(scalar_type(name.as_str(), data_connector_name));
+        }
+    }
+
+    for (name, object_type) in &schema.object_types {
+        if !response_types.contains_key(name.as_str()) {
+            documents.extend(object_type_documents(
+                name.as_str(),
+                object_type,
+                data_connector_name,
+            ));
+        }
+    }
+
+    for function in &schema.functions {
+        documents.extend(command(
+            &Command {
+                name: function.name.as_str(),
+                description: function.description.as_deref(),
+                arguments: &function.arguments,
+                output_type: output_type(&function.result_type),
+                data_connector_command: json!({ "function": function.name }),
+                root_field_kind: "Query",
+            },
+            request,
+            data_connector_name,
+        ));
+    }
+    for procedure in &schema.procedures {
+        documents.extend(command(
+            &Command {
+                name: procedure.name.as_str(),
+                description: procedure.description.as_deref(),
+                arguments: &procedure.arguments,
+                output_type: output_type(&procedure.result_type),
+                data_connector_command: json!({ "procedure": procedure.name }),
+                root_field_kind: "Mutation",
+            },
+            request,
+            data_connector_name,
+        ));
+    }
+
+    documents
+}
+
+/// Header forwarding settings, to be merged into the existing data connector link
+fn data_connector_link(
+    request: &RequestConfig,
+    response: &ResponseConfig,
+    data_connector_name: &str,
+) -> Option<MetadataDocument> {
+    if request.forward_headers.is_empty() && response.forward_headers.is_empty() {
+        return None;
+    }
+
+    let mut comments = vec![format!(
+        "Merge argumentPresets and responseHeaders into the definition of DataConnectorLink {data_connector_name}"
+    )];
+    let mut definition = json!({ "name": data_connector_name });
+
+    if !request.forward_headers.is_empty() {
+        let (forward, patterns) = header_names(&request.forward_headers);
+        if !patterns.is_empty() {
+            comments.push(format!(
+                "DDN forwards headers by name, add the request headers matching {} to httpHeaders.forward",
+                patterns.join(", ")
+            ));
+        }
+        definition["argumentPresets"] = json!([{
+            "argument": request.headers_argument,
+            "value": {
+                "httpHeaders": {
+                    "forward": forward,
+                    "additional": {},
+                },
+            },
+        }]);
+    }
+
+    if !response.forward_headers.is_empty() {
+        let (forward, patterns) = header_names(&response.forward_headers);
+        if !patterns.is_empty() {
+            comments.push(format!(
+                "DDN forwards headers by name, add the response headers matching {} to responseHeaders.forwardHeaders",
+                patterns.join(", ")
+            ));
+        }
+        definition["responseHeaders"] = json!({
+            "headersField": response.headers_field,
+            "resultField": response.response_field,
+            "forwardHeaders": forward,
+        });
+    }
+
+    Some(MetadataDocument {
+        comments,
+        value: json!({
+            "kind": "DataConnectorLink",
+            "version": "v1",
+            "definition": definition,
+        }),
+    })
+}
+
+/// Split forwarded headers into exact names, which DDN can forward, and glob patterns, which it cannot
+fn header_names(forward_headers: &[String]) -> (Vec<&str>, Vec<&str>) {
+    forward_headers
+        .iter()
+        .map(String::as_str)
+        .partition(|header| !header.contains(['*', '?', '[', '{']))
+}
+
+fn scalar_type(name: &str, data_connector_name: &str) -> Vec<MetadataDocument> {
+    let mut documents = vec![];
+
+    if !DDN_BUILT_IN_SCALARS.contains(&name) {
+        documents.push(MetadataDocument {
+            comments: vec![],
+            value: json!({
+                "kind": "ScalarType",
+                "version": "v1",
+                "definition": {
+                    "name": name,
+                    "graphql": { "typeName": name },
+                },
+            }),
+        });
+    }
+
+    documents.push(MetadataDocument {
+        comments: vec![],
+        value: json!({
+            "kind": "DataConnectorScalarRepresentation",
+            "version": "v1",
+            "definition": {
+                "dataConnectorName": data_connector_name,
+                "dataConnectorScalarType": name,
+                "representation": name,
+                "graphql": { "comparisonExpressionTypeName": format!("{name}ComparisonExp") },
+            },
+        }),
+    });
+
+    documents
+}
+
+fn object_type_documents(
+    name: &str,
+    object_type: &models::ObjectType,
+    data_connector_name: &str,
+) -> Vec<MetadataDocument> {
+    let fields: Vec<Value> = object_type
+        .fields
+        .iter()
+        .map(|(field_name, field)| {
+            let mut field_definition = json!({
+                "name": field_name,
+                "type": ddn_type(&field.r#type),
+            });
+            if let Some(description) = &field.description {
+                field_definition["description"] = json!(description);
+            }
+            if !field.arguments.is_empty() {
+                field_definition["arguments"] = json!(arguments(&field.arguments, None));
+            }
+            field_definition
+        })
+        .collect();
+    let field_mapping: BTreeMap<_, _> = object_type
+        .fields
+        .keys()
+        .map(|field_name| (field_name, json!({ "column": { "name": field_name } })))
+        .collect();
+
+    let mut definition = json!({
+        "name": name,
+        "fields": fields,
+        "graphql": {
+            "typeName": name,
+            "inputTypeName": format!("{name}Input"),
+        },
+        "dataConnectorTypeMapping": [{
+            "dataConnectorName": data_connector_name,
+            "dataConnectorObjectType": name,
+            "fieldMapping": field_mapping,
+        }],
+    });
+    if let Some(description) = &object_type.description {
+        definition["description"] = json!(description);
+    }
+
+    vec![
+        MetadataDocument {
+            comments: vec![],
+            value: json!({
+                "kind": "ObjectType",
+                "version": "v1",
+                "definition": definition,
+            }),
+        },
+        MetadataDocument {
+            comments: vec![],
+            value: json!({
+                "kind": "TypePermissions",
+                "version": "v1",
+                "definition": {
+                    "typeName": name,
+                    "permissions": [{
+                        "role": ADMIN_ROLE,
+                        "output": { "allowedFields": object_type.fields.keys().collect::<Vec<_>>() },
+                    }],
+                },
+            }),
+        },
+    ]
+}
+
+/// A function or procedure of the NDC schema, to generate a DDN command for
+struct Command<'a> {
+    name: &'a str,
+    description: Option<&'a str>,
+    arguments: &'a BTreeMap<ArgumentName, ArgumentInfo>,
+    output_type: String,
+    data_connector_command: Value,
+    root_field_kind: &'static str,
+}
+
+fn command(
+    command: &Command,
+    request: &RequestConfig,
+    data_connector_name: &str,
+) -> Vec<MetadataDocument> {
+    let name = command.name;
+    let mut definition = json!({
+        "name": name,
+        "outputType": command.output_type,
+        // the headers argument is set by the argument preset of the data connector link
+        "arguments": arguments(command.arguments, Some(&request.headers_argument)),
+        "source": {
+            "dataConnectorName": data_connector_name,
+            "dataConnectorCommand": command.data_connector_command,
+        },
+        "graphql": {
+            "rootFieldName": name,
+            "rootFieldKind": command.root_field_kind,
+        },
+    });
+    if let Some(description) = command.description {
+        definition["description"] = json!(description);
+    }
+
+    vec![
+        MetadataDocument {
+            comments: vec![],
+            value: json!({
+                "kind": "Command",
+                "version": "v1",
+                "definition": definition,
+            }),
+        },
+        MetadataDocument {
+            comments: vec![],
+            value: json!({
+                "kind": "CommandPermissions",
+                "version": "v1",
+                "definition": {
+                    "commandName": name,
+                    "permissions": [{ "role": ADMIN_ROLE, "allowExecution": true }],
+                },
+            }),
+        },
+    ]
+}
+
+fn arguments(
+    arguments: &BTreeMap<ArgumentName, ArgumentInfo>,
+    preset_argument: Option<&ArgumentName>,
+) -> Vec<Value> {
+    arguments
+        .iter()
+        .filter(|(name, _)| preset_argument != Some(*name))
+        .map(|(name, argument)| {
+            let mut argument_definition = json!({
+                "name": name,
+                "type": ddn_type(&argument.argument_type),
+            });
+            if let Some(description) = &argument.description {
+                argument_definition["description"] = json!(description);
+            }
+            argument_definition
+        })
+        .collect()
+}
+
+/// DDN type references use GraphQL syntax, eg. `[String!]`
+fn ddn_type(r#type: &models::Type) -> String {
+    match r#type {
+        models::Type::Nullable { underlying_type } => {
+            let underlying_type = ddn_type(underlying_type);
+            underlying_type
+                .strip_suffix('!')
+                .map_or(underlying_type.clone(), ToOwned::to_owned)
+        }
+        models::Type::Named { name } => format!("{name}!"),
+        models::Type::Array { element_type } => format!("[{}]!", ddn_type(element_type)),
+        models::Type::Predicate { object_type_name } => format!("{object_type_name}!"),
+    }
+}
+
+#[test]
+fn test_ddn_metadata() {
+    use common::{
+        config::{schema::SchemaDefinition, SchemaConfig},
+        schema_response::schema_response,
+    };
+    use std::collections::BTreeSet;
+
+    let schema_document = common::config::schema::parse_schema(
+        r#"
+        type Query {
+          "Look up a user"
+          user(id: ID!): User
+        }
+        type Mutation {
+          createUser(name: String!, tags: [String!]): User!
+        }
+        type User {
+          id: ID!
+          name: String
+        }
+        "#,
+    )
+    .unwrap();
+
+    let schema_config = SchemaConfig {
+        request: RequestConfig {
+            forward_headers: vec!["Authorization".to_string(), "x-hasura-*".to_string()],
+            ..RequestConfig::default()
+        },
+        response: ResponseConfig {
+            forward_headers: vec!["Set-Cookie".to_string()],
+            ..ResponseConfig::default()
+        },
+        ..SchemaConfig::default()
+    };
+    let schema = SchemaDefinition::new(&schema_document, &schema_config).unwrap();
+    let schema = schema_response(
+        &schema,
+        &schema_config.request,
+        &schema_config.response,
+        &schema_config.naming,
+    );
+
+    let documents = ddn_metadata(
+        &schema,
+        &schema_config.request,
+        &schema_config.response,
+        "crm",
+    );
+
+    let link = &documents[0];
+    assert_eq!(
+        link.comments[1],
+        "DDN forwards headers by name, add the request headers matching x-hasura-* to httpHeaders.forward"
+    );
+    assert_eq!(
+        link.value["definition"]["argumentPresets"],
+        json!([{
+            "argument": "_headers",
+            "value": { "httpHeaders": { "forward": ["Authorization"], "additional": {} } },
+        }])
+    );
+    assert_eq!(
+        link.value["definition"]["responseHeaders"],
+        json!({
+            "headersField": "headers",
+            "resultField": "response",
+            "forwardHeaders": ["Set-Cookie"],
+        })
+    );
+
+    let kinds: BTreeSet<(&str, &str)> = documents
+        .iter()
+        .filter_map(|document| {
+            let definition = &document.value["definition"];
+            let name = definition["name"]
+                .as_str()
+                .or(definition["typeName"].as_str())
+                .or(definition["commandName"].as_str())
+                .or(definition["dataConnectorScalarType"].as_str())?;
+            Some((document.value["kind"].as_str()?, name))
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        BTreeSet::from([
+            ("DataConnectorLink", "crm"),
+            ("ScalarType", "ID"),
+            ("DataConnectorScalarRepresentation", "ID"),
+            ("DataConnectorScalarRepresentation", "String"),
+            ("ObjectType", "User"),
+            ("TypePermissions", "User"),
+            ("Command", "user"),
+            ("CommandPermissions", "user"),
+            ("Command", "createUser"),
+            ("CommandPermissions", "createUser"),
+        ]),
+        "response types and the headers scalar should not have metadata"
+    );
+
+    let create_user = documents
+        .iter()
+        .find(|document| document.value["definition"]["name"] == "createUser")
+        .unwrap();
+    assert_eq!(
+        create_user.value["definition"]["outputType"], "User!",
+        "commands should return the response field of response types"
+    );
+    assert_eq!(
+        create_user.value["definition"]["arguments"],
+        json!([
+            { "name": "name", "type": "String!" },
+            { "name": "tags", "type": "[String!]" },
+        ]),
+        "the headers argument should be preset, not a command argument"
+    );
+}
@@ -0,0 +1,472 @@
+use graphql_parser::query::{
+    self, Definition, Document, OperationDefinition, Selection, SelectionSet, Value,
+};
+use ndc_models::{self as models, ArgumentInfo, ArgumentName, SchemaResponse};
+use serde_json::{json, Map};
+use std::{collections::BTreeMap, fmt::Display};
+
+/// An NDC request as JSON, in the format of the test fixtures
+#[derive(Debug, Clone, PartialEq)]
+pub enum NdcRequest {
+    Query(serde_json::Value),
+    Mutation(serde_json::Value),
+}
+
+#[derive(Debug)]
+pub enum OperationError {
+    Parse(String),
+    OperationCount(usize),
+    FragmentsNotSupported,
+    SubscriptionsNotSupported,
+    RootFieldCount(usize),
+    UnknownFunction(String),
+    UnknownProcedure(String),
+    UnknownField { object_type: String, field: String },
+    UnknownArgument { field: String, argument: String },
+    MissingSelection { field: String, object_type: String },
+    SelectionOnScalar { field: String, scalar_type: String },
+    MissingVariable(String),
+}
+
+impl std::error::Error for OperationError {}
+
+impl Display for OperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationError::Parse(err) => write!(f, "Could not parse operation: {err}"),
+            OperationError::OperationCount(count) => {
+                write!(f, "Expected a single operation, got {count}")
+            }
+            OperationError::FragmentsNotSupported => {
+                write!(f, "Fragments are not supported, select fields directly")
+            }
+            OperationError::SubscriptionsNotSupported => {
+                write!(f, "Subscriptions are not supported")
+            }
+            OperationError::RootFieldCount(count) => {
+                write!(f, "Queries must select exactly one function, got {count}")
+            }
+            OperationError::UnknownFunction(name) => write!(
+                f,
+                "Unknown function {name}, expected a function of the NDC schema"
+            ),
+            OperationError::UnknownProcedure(name) => write!(
+                f,
+                "Unknown procedure {name}, expected a procedure of the NDC schema"
+            ),
+            OperationError::UnknownField { object_type, field } => {
+                write!(f, "Unknown field {field} on object type {object_type}")
+            }
+            OperationError::UnknownArgument { field, argument } => {
+                write!(f, "Unknown argument {argument} on {field}")
+            }
+            OperationError::MissingSelection { field, object_type } => write!(
+                f,
+                "Field {field} of object type {object_type} must have a selection of fields"
+            ),
+            OperationError::SelectionOnScalar { field, scalar_type } => write!(
+                f,
+                "Field {field} of scalar type {scalar_type} cannot have a selection of fields"
+            ),
+            OperationError::MissingVariable(name) => {
+                write!(f, "Variable ${name} has no value and no default value")
+            }
+        }
+    }
+}
+
+/// Translate a GraphQL operation against the NDC schema into an NDC request.
+/// Query operations select a single function, mutation operations select procedures, each becoming an operation.
+pub fn ndc_request(
+    schema: &SchemaResponse,
+    operation: &str,
+    variables: &Map<String, serde_json::Value>,
+) -> Result<NdcRequest, OperationError> {
+    let document: Document<String> =
+        query::parse_query(operation).map_err(|err| OperationError::Parse(err.to_string()))?;
+
+    let operation = match document.definitions.as_slice() {
+        [Definition::Operation(operation)] => operation,
+        [Definition::Fragment(_)] => return Err(OperationError::FragmentsNotSupported),
+        definitions => return Err(OperationError::OperationCount(definitions.len())),
+    };
+
+    match operation {
+        OperationDefinition::SelectionSet(selection_set) => {
+            let translator = Translator {
+                schema,
+                variables: variables.clone(),
+            };
+            translator.query_request(selection_set)
+        }
+        OperationDefinition::Query(query) => {
+            let translator = Translator::new(schema, &query.variable_definitions, variables)?;
+            translator.query_request(&query.selection_set)
+        }
+        OperationDefinition::Mutation(mutation) => {
+            let translator = Translator::new(schema, &mutation.variable_definitions, variables)?;
+            translator.mutation_request(&mutation.selection_set)
+        }
+        OperationDefinition::Subscription(_) => Err(OperationError::SubscriptionsNotSupported),
+    }
+}
+
+struct Translator<'a> {
+    schema: &'a SchemaResponse,
+    /// variable values, including defaults for variables not given a value
+    variables: Map<String, serde_json::Value>,
+}
+
+impl<'a> Translator<'a> {
+    fn new(
+        schema: &'a SchemaResponse,
+        variable_definitions: &[query::VariableDefinition<String>],
+        variables: &Map<String, serde_json::Value>,
+    ) -> Result<Self, OperationError> {
+        let mut variables = variables.clone();
+        for definition in variable_definitions {
+            if !variables.contains_key(&definition.name) {
+                if let Some(default_value) = &definition.default_value {
+                    let default_value = json_value(default_value, &Map::new())?;
+                    variables.insert(definition.name.to_owned(), default_value);
+                }
+            }
+        }
+        Ok(Self { schema, variables })
+    }
+    fn query_request(
+        &self,
+        selection_set: &SelectionSet<String>,
+    ) -> Result<NdcRequest, OperationError> {
+        let fields = root_fields(selection_set)?;
+        let [field] = fields.as_slice() else {
+            return Err(OperationError::RootFieldCount(fields.len()));
+        };
+
+        let function = self
+            .schema
+            .functions
+            .iter()
+            .find(|function| function.name.as_str() == field.name)
+            .ok_or_else(|| OperationError::UnknownFunction(field.name.to_owned()))?;
+
+        let arguments: Map<_, _> = self
+            .arguments(&field.name, &function.arguments, &field.arguments)?
+            .into_iter()
+            .map(|(name, value)| (name, json!({ "type": "literal", "value": value })))
+            .collect();
+
+        Ok(NdcRequest::Query(json!({
+            "collection": function.name,
+            "query": {
+                "fields": {
+                    "__value": {
+                        "type": "column",
+                        "column": "__value",
+                        "fields": self.nested_field(&field.name, &function.result_type, &field.selection_set)?,
+                    },
+                },
+            },
+            "arguments": arguments,
+            "collection_relationships": {},
+        })))
+    }
+    fn mutation_request(
+        &self,
+        selection_set: &SelectionSet<String>,
+    ) -> Result<NdcRequest, OperationError> {
+        let operations = root_fields(selection_set)?
+            .into_iter()
+            .map(|field| {
+                let procedure = self
+                    .schema
+                    .procedures
+                    .iter()
+                    .find(|procedure| procedure.name.as_str() == field.name)
+                    .ok_or_else(|| OperationError::UnknownProcedure(field.name.to_owned()))?;
+
+                Ok(json!({
+                    "type": "procedure",
+                    "name": procedure.name,
+                    "arguments": self.arguments(&field.name, &procedure.arguments, &field.arguments)?,
+                    "fields": self.nested_field(&field.name, &procedure.result_type, &field.selection_set)?,
+                }))
+            })
+            .collect::<Result<Vec<_>, OperationError>>()?;
+
+        Ok(NdcRequest::Mutation(json!({
+            "operations": operations,
+            "collection_relationships": {},
+        })))
+    }
+    fn arguments(
+        &self,
+        field_name: &str,
+        argument_definitions: &BTreeMap<ArgumentName, ArgumentInfo>,
+        arguments: &[(String, Value<String>)],
+    ) -> Result<Map<String, serde_json::Value>, OperationError> {
+        arguments
+            .iter()
+            .map(|(name, value)| {
+                if !argument_definitions.contains_key(name.as_str()) {
+                    return Err(OperationError::UnknownArgument {
+                        field: field_name.to_owned(),
+                        argument: name.to_owned(),
+                    });
+                }
+                Ok((name.to_owned(), json_value(value, &self.variables)?))
+            })
+            .collect()
+    }
+    /// The nested field selecting from a value of the given type, null for scalars
+    fn nested_field(
+        &self,
+        field_name: &str,
+        r#type: &models::Type,
+        selection_set: &SelectionSet<String>,
+    ) -> Result<serde_json::Value, OperationError> {
+        match r#type {
+            models::Type::Nullable { underlying_type } => {
+                self.nested_field(field_name, underlying_type, selection_set)
+            }
+            models::Type::Array { element_type } => {
+                let fields = self.nested_field(field_name, element_type, selection_set)?;
+                Ok(if fields.is_null() {
+                    fields
+                } else {
+                    json!({ "type": "array", "fields": fields })
+                })
+            }
+            models::Type::Named { name } => match self.schema.object_types.get(name.as_str()) {
+                Some(object_type) => {
+                    if selection_set.items.is_empty() {
+                        return Err(OperationError::MissingSelection {
+                            field: field_name.to_owned(),
+                            object_type: name.to_string(),
+                        });
+                    }
+                    self.nested_object(name.as_str(), object_type, selection_set)
+                }
+                None if selection_set.items.is_empty() => Ok(serde_json::Value::Null),
+                None => Err(OperationError::SelectionOnScalar {
+                    field: field_name.to_owned(),
+                    scalar_type: name.to_string(),
+                }),
+            },
+            models::Type::Predicate { object_type_name } => {
+                Err(OperationError::SelectionOnScalar {
+                    field: field_name.to_owned(),
+                    scalar_type: object_type_name.to_string(),
+                })
+            }
+        }
+    }
+    fn nested_object(
+        &self,
+        object_type_name: &str,
+        object_type: &models::ObjectType,
+        selection_set: &SelectionSet<String>,
+    ) -> Result<serde_json::Value, OperationError> {
+        let mut fields = Map::new();
+
+        for field in root_fields(selection_set)? {
+            let field_definition =
+                object_type.fields.get(field.name.as_str()).ok_or_else(|| {
+                    OperationError::UnknownField {
+                        object_type: object_type_name.to_owned(),
+                        field: field.name.to_owned(),
+                    }
+                })?;
+
+            let field_path = format!("{object_type_name}.{}", field.name);
+            let mut column = json!({
+                "type": "column",
+                "column": field.name,
+                "fields": self.nested_field(&field_path, &field_definition.r#type, &field.selection_set)?,
+            });
+
+            let arguments: Map<_, _> = self
+                .arguments(&field_path, &field_definition.arguments, &field.arguments)?
+                .into_iter()
+                .map(|(name, value)| (name, json!({ "type": "literal", "value": value })))
+                .collect();
+            if !arguments.is_empty() {
+                column["arguments"] = arguments.into();
+            }
+
+            let alias = field.alias.as_ref().unwrap_or(&field.name);
+            fields.insert(alias.to_owned(), column);
+        }
+
+        Ok(json!({ "type": "object", "fields": fields }))
+    }
+}
+
+fn root_fields<'a, 'b>(
+    selection_set: &'b SelectionSet<'a, String>,
+) -> Result<Vec<&'b query::Field<'a, String>>, OperationError> {
+    selection_set
+        .items
+        .iter()
+        .map(|selection| match selection {
+            Selection::Field(field) => Ok(field),
+            Selection::FragmentSpread(_) | Selection::InlineFragment(_) => {
+                Err(OperationError::FragmentsNotSupported)
+            }
+        })
+        .collect()
+}
+
+fn json_value(
+    value: &Value<String>,
+    variables: &Map<String, serde_json::Value>,
+) -> Result<serde_json::Value, OperationError> {
+    Ok(match value {
+        Value::Variable(name) => variables
+            .get(name)
+            .cloned()
+            .ok_or_else(|| OperationError::MissingVariable(name.to_owned()))?,
+        Value::Int(number) => number.as_i64().map_or(serde_json::Value::Null, Into::into),
+        Value::Float(number) => (*number).into(),
+        Value::String(string) | Value::Enum(string) => string.to_owned().into(),
+        Value::Boolean(boolean) => (*boolean).into(),
+        Value::Null => serde_json::Value::Null,
+        Value::List(list) => list
+            .iter()
+            .map(|value| json_value(value, variables))
+            .collect::<Result<_, _>>()?,
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| Ok((key.to_owned(), json_value(value, variables)?)))
+            .collect::<Result<Map<_, _>, _>>()?
+            .into(),
+    })
+}
+
+#[test]
+fn test_ndc_request() {
+    use common::{
+        config::{
+            schema::{parse_schema, SchemaDefinition},
+            SchemaConfig,
+        },
+        schema_response::schema_response,
+    };
+
+    let schema_document = parse_schema(
+        r"
+        type Query {
+          users(limit: Int, where: UserFilter): [User!]!
+        }
+        type Mutation {
+          deleteUser(id: ID!): User
+        }
+        type User {
+          id: ID!
+          name: String
+          posts(first: Int): [Post!]
+        }
+        type Post {
+          title: String
+        }
+        input UserFilter {
+          name: String
+        }
+        ",
+    )
+    .unwrap();
+    let schema_config = SchemaConfig::default();
+    let schema = SchemaDefinition::new(&schema_document, &schema_config).unwrap();
+    let schema = schema_response(
+        &schema,
+        &schema_config.request,
+        &schema_config.response,
+        &schema_config.naming,
+    );
+
+    let variables = json!({ "name": "Ada" }).as_object().unwrap().clone();
+    let query = ndc_request(
+        &schema,
+        r"query ($name: String, $limit: Int = 10) {
+          users(limit: $limit, where: { name: $name }) {
+            id
+            recentPosts: posts(first: 2) { title }
+          }
+        }",
+        &variables,
+    )
+    .unwrap();
+
+    assert_eq!(
+        query,
+        NdcRequest::Query(json!({
+            "collection": "users",
+            "query": {
+                "fields": {
+                    "__value": {
+                        "type": "column",
+                        "column": "__value",
+                        "fields": {
+                            "type": "array",
+                            "fields": {
+                                "type": "object",
+                                "fields": {
+                                    "id": { "type": "column", "column": "id", "fields": null },
+                                    "recentPosts": {
+                                        "type": "column",
+                                        "column": "posts",
+                                        "fields": {
+                                            "type": "array",
+                                            "fields": {
+                                                "type": "object",
+                                                "fields": {
+                                                    "title": { "type": "column", "column": "title", "fields": null },
+                                                },
+                                            },
+                                        },
+                                        "arguments": { "first": { "type": "literal", "value": 2 } },
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+            "arguments": {
+                "limit": { "type": "literal", "value": 10 },
+                "where": { "type": "literal", "value": { "name": "Ada" } },
+            },
+            "collection_relationships": {},
+        }))
+    );
+
+    let mutation = ndc_request(
+        &schema,
+        r#"mutation { deleteUser(id: "1") { id } }"#,
+        &Map::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        mutation,
+        NdcRequest::Mutation(json!({
+            "operations": [{
+                "type": "procedure",
+                "name": "deleteUser",
+                "arguments": { "id": "1" },
+                "fields": {
+                    "type": "object",
+                    "fields": { "id": { "type": "column", "column": "id", "fields": null } },
+                },
+            }],
+            "collection_relationships": {},
+        }))
+    );
+
+    let error = ndc_request(&schema, "{ users { id email } }", &Map::new()).unwrap_err();
+    assert_eq!(error.to_string(), "Unknown field email on object type User");
+    let error = ndc_request(&schema, "{ users }", &Map::new()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Field users of object type User must have a selection of fields"
+    );
+}
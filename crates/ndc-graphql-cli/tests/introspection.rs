@@ -1,21 +1,100 @@
+use common::graphql::{
+    introspection::Introspection, resolve_truncated_type_refs, schema_from_introspection,
+    IntrospectionFeatures,
+};
 use insta::assert_snapshot;
-use ndc_graphql_cli::graphql::{introspection::Introspection, schema_from_introspection};
 use std::{error::Error, path::PathBuf};
 use tokio::fs;
 
-#[tokio::test]
-async fn generate_schema_from_introspection() -> Result<(), Box<dyn Error>> {
-    let introspection_response_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+async fn read_test_file(file_name: &str) -> Result<String, Box<dyn Error>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("tests")
-        .join("introspection.json");
-    let introspection_file = fs::read_to_string(introspection_response_path).await?;
+        .join(file_name);
+    Ok(fs::read_to_string(path).await?)
+}
+
+async fn read_introspection(file_name: &str) -> Result<Introspection, Box<dyn Error>> {
+    let introspection_file = read_test_file(file_name).await?;
     let introspection_response: graphql_client::Response<Introspection> =
         serde_json::from_str(&introspection_file)?;
-    let introspection_data = introspection_response
+    Ok(introspection_response
         .data
-        .expect("introspection test file should have data");
-    let graphql_sdl = schema_from_introspection(introspection_data);
+        .expect("introspection test file should have data"))
+}
+
+#[tokio::test]
+async fn generate_schema_from_introspection() -> Result<(), Box<dyn Error>> {
+    let introspection_data = read_introspection("introspection.json").await?;
+    let graphql_sdl = schema_from_introspection(introspection_data)?;
+    assert_snapshot!(graphql_sdl.to_string());
+
+    Ok(())
+}
+
+/// modern_introspection.json is the introspection of modern_schema.graphql,
+/// using the October 2021 spec, plus isOneOf.
+/// Post.matrix is nested deeper than the introspection query goes,
+/// so Post is introspected again, with the response in modern_introspection_post.json.
+/// Every definition in the original schema should be reproduced exactly.
+#[tokio::test]
+async fn generate_schema_from_modern_introspection() -> Result<(), Box<dyn Error>> {
+    let mut introspection_data = read_introspection("modern_introspection.json").await?;
+    let post_response = read_test_file("modern_introspection_post.json").await?;
+
+    resolve_truncated_type_refs(
+        &mut introspection_data,
+        &IntrospectionFeatures::all(),
+        |_query, variables| {
+            assert_eq!(
+                variables["name"], "Post",
+                "only Post should be re-introspected"
+            );
+            let response = serde_json::from_str(&post_response);
+            async move { Ok(response?) }
+        },
+    )
+    .await?;
+
+    let graphql_sdl = schema_from_introspection(introspection_data)?;
     assert_snapshot!(graphql_sdl.to_string());
 
+    let generated_definitions: Vec<String> = graphql_sdl
+        .definitions
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    let known_schema = read_test_file("modern_schema.graphql").await?;
+    let known_schema = graphql_parser::parse_schema::<String>(&known_schema)?;
+
+    for definition in &known_schema.definitions {
+        let definition = definition.to_string();
+        assert!(
+            generated_definitions.contains(&definition),
+            "generated schema should contain {definition}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn depth_limited_introspection() -> Result<(), Box<dyn Error>> {
+    let mut introspection_data = read_introspection("modern_introspection.json").await?;
+
+    let error = resolve_truncated_type_refs(
+        &mut introspection_data,
+        &IntrospectionFeatures::all(),
+        |_query, _variables| async {
+            Ok(serde_json::from_value(serde_json::json!({
+                "errors": [{ "message": "query exceeds maximum depth" }]
+            }))?)
+        },
+    )
+    .await
+    .expect_err("introspection should fail");
+
+    assert_snapshot!(error.to_string());
+
     Ok(())
 }
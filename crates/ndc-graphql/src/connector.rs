@@ -8,8 +8,12 @@ use ndc_sdk::{
     models,
 };
 use query::{handle_query, handle_query_explain};
+mod drift;
 mod mutation;
+mod predicate;
 mod query;
+mod reload;
+mod response_validation;
 pub mod setup;
 mod state;
 
@@ -47,46 +51,51 @@ impl Connector for GraphQLConnector {
             &configuration.schema,
             &configuration.request,
             &configuration.response,
+            &configuration.naming,
         )))
     }
 
     async fn query_explain(
-        configuration: &Self::Configuration,
+        _configuration: &Self::Configuration,
         state: &Self::State,
         request: models::QueryRequest,
     ) -> connector::Result<JsonResponse<models::ExplainResponse>> {
+        let configuration = state.configuration().await;
         Ok(JsonResponse::Value(
-            handle_query_explain(configuration, state, request).await?,
+            handle_query_explain(&configuration, state, request).await?,
         ))
     }
 
     async fn mutation_explain(
-        configuration: &Self::Configuration,
+        _configuration: &Self::Configuration,
         state: &Self::State,
         request: models::MutationRequest,
     ) -> connector::Result<JsonResponse<models::ExplainResponse>> {
+        let configuration = state.configuration().await;
         Ok(JsonResponse::Value(
-            handle_mutation_explain(configuration, state, request).await?,
+            handle_mutation_explain(&configuration, state, request).await?,
         ))
     }
 
     async fn mutation(
-        configuration: &Self::Configuration,
+        _configuration: &Self::Configuration,
         state: &Self::State,
         request: models::MutationRequest,
     ) -> connector::Result<JsonResponse<models::MutationResponse>> {
+        let configuration = state.configuration().await;
         Ok(JsonResponse::Value(
-            handle_mutation(configuration, state, request).await?,
+            handle_mutation(&configuration, state, request).await?,
         ))
     }
 
     async fn query(
-        configuration: &Self::Configuration,
+        _configuration: &Self::Configuration,
         state: &Self::State,
         request: models::QueryRequest,
     ) -> connector::Result<JsonResponse<models::QueryResponse>> {
+        let configuration = state.configuration().await;
         Ok(JsonResponse::Value(
-            handle_query(configuration, state, request).await?,
+            handle_query(&configuration, state, request).await?,
         ))
     }
 }
@@ -0,0 +1,131 @@
+use super::reload::SharedConfig;
+use common::{
+    config::ServerConfig,
+    graphql::{execute_graphql_introspection, schema_from_introspection},
+    schema_diff::{diff_schemas, SchemaChange},
+};
+use prometheus::{IntCounter, IntGauge, Registry};
+use std::time::Duration;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+/// Metrics for the differences between the configured schema and the live upstream schema
+#[derive(Debug, Clone)]
+pub struct SchemaDriftMetrics {
+    breaking_changes: IntGauge,
+    non_breaking_changes: IntGauge,
+    failed_checks: IntCounter,
+}
+
+impl SchemaDriftMetrics {
+    pub fn new(registry: &mut Registry) -> Result<Self, prometheus::Error> {
+        let breaking_changes = IntGauge::new(
+            "ndc_graphql_schema_drift_breaking_changes",
+            "Breaking differences between the configured schema and the live upstream schema",
+        )?;
+        let non_breaking_changes = IntGauge::new(
+            "ndc_graphql_schema_drift_non_breaking_changes",
+            "Non-breaking differences between the configured schema and the live upstream schema",
+        )?;
+        let failed_checks = IntCounter::new(
+            "ndc_graphql_schema_drift_failed_checks_total",
+            "Schema drift checks that failed to introspect the upstream schema",
+        )?;
+
+        registry.register(Box::new(breaking_changes.clone()))?;
+        registry.register(Box::new(non_breaking_changes.clone()))?;
+        registry.register(Box::new(failed_checks.clone()))?;
+
+        Ok(Self {
+            breaking_changes,
+            non_breaking_changes,
+            failed_checks,
+        })
+    }
+}
+
+/// Background task comparing the configured schema with the live upstream schema, aborted when dropped
+#[derive(Debug)]
+pub struct SchemaDriftTask(JoinHandle<()>);
+
+impl SchemaDriftTask {
+    pub fn spawn(config: SharedConfig, interval: Duration, metrics: SchemaDriftMetrics) -> Self {
+        Self(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // only log differences when they change, not on every check
+            let mut reported_changes = vec![];
+
+            loop {
+                interval.tick().await;
+
+                let current_config = config.get().await;
+                let changes = match check_schema_drift(&current_config).await {
+                    Ok(changes) => changes,
+                    Err(err) => {
+                        metrics.failed_checks.inc();
+                        tracing::warn!("Schema drift check failed: {err}");
+                        continue;
+                    }
+                };
+
+                let breaking_changes = changes.iter().filter(|change| change.breaking).count();
+                metrics
+                    .breaking_changes
+                    .set(i64::try_from(breaking_changes).unwrap_or(i64::MAX));
+                metrics
+                    .non_breaking_changes
+                    .set(i64::try_from(changes.len() - breaking_changes).unwrap_or(i64::MAX));
+
+                if changes != reported_changes {
+                    report_schema_drift(&changes);
+                    reported_changes = changes;
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for SchemaDriftTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+async fn check_schema_drift(config: &ServerConfig) -> Result<Vec<SchemaChange>, String> {
+    let response = execute_graphql_introspection(&config.connection)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if let Some(errors) = response.errors {
+        return Err(format!(
+            "Introspection error: {}",
+            serde_json::to_string(&errors).map_err(|err| err.to_string())?
+        ));
+    }
+
+    let introspection = response
+        .data
+        .ok_or_else(|| "Introspection response has no data".to_owned())?;
+    let live_schema = schema_from_introspection(introspection)
+        .map_err(|err| format!("Live schema is invalid: {err}"))?;
+    let live_schema = config
+        .schema_definition(&live_schema)
+        .map_err(|errors| format!("Live schema is invalid: {errors}"))?;
+
+    Ok(diff_schemas(&config.schema, &live_schema))
+}
+
+fn report_schema_drift(changes: &[SchemaChange]) {
+    if changes.is_empty() {
+        tracing::info!("Live upstream schema matches the configured schema");
+        return;
+    }
+
+    for change in changes {
+        if change.breaking {
+            tracing::warn!("Schema drift (breaking): {change}");
+        } else {
+            tracing::info!("Schema drift: {change}");
+        }
+    }
+}
@@ -1,8 +1,11 @@
-use super::state::ServerState;
+use super::{
+    response_validation::{ResponseValidationError, ResponseValidator},
+    state::ServerState,
+};
 use crate::query_builder::build_mutation_document;
 use common::{
     client::{execute_graphql, GraphQLRequest},
-    config::ServerConfig,
+    config::{config_file::ResponseValidation, ServerConfig},
 };
 use indexmap::IndexMap;
 use ndc_sdk::{connector::MutationError, models};
@@ -54,6 +57,12 @@ pub async fn handle_mutation(
         .await
         .map_err(|err| MutationError::new_invalid_request(&err))?;
 
+    let mut request_headers = operation.headers;
+    state
+        .authorize(&mut request_headers, &client, &configuration.connection)
+        .await
+        .map_err(|err| MutationError::new_invalid_request(&err))?;
+
     let execution_span =
         tracing::info_span!("Execute GraphQL Mutation", internal.visibility = "user");
 
@@ -61,7 +70,7 @@ pub async fn handle_mutation(
         &operation.query,
         operation.variables,
         &configuration.connection.endpoint,
-        &operation.headers,
+        &request_headers,
         &client,
         &configuration.response.forward_headers,
     )
@@ -74,6 +83,10 @@ pub async fn handle_mutation(
             Err(MutationError::new_unprocessable_content(&errors[0].message)
                 .with_details(serde_json::json!({ "errors": errors })))
         } else if let Some(mut data) = response.data {
+            if configuration.response.validation != ResponseValidation::Disabled {
+                validate_response(&mut data, &request, configuration)?;
+            }
+
             let forward_response_headers = !configuration.response.forward_headers.is_empty();
 
             let operation_results = request
@@ -112,3 +125,37 @@ pub async fn handle_mutation(
         }
     })
 }
+
+fn validate_response(
+    data: &mut IndexMap<String, serde_json::Value>,
+    request: &models::MutationRequest,
+    configuration: &ServerConfig,
+) -> Result<(), MutationError> {
+    let mut validator = ResponseValidator::new(&configuration.schema);
+
+    for (index, operation) in request.operations.iter().enumerate() {
+        match operation {
+            models::MutationOperation::Procedure { name, fields, .. } => {
+                let alias = format!("procedure_{index}");
+                let Some(root_field) = configuration.procedure_field(name.as_str()) else {
+                    continue;
+                };
+                if let Some(value) = data.get_mut(&alias) {
+                    validator.validate_root_field(
+                        &alias,
+                        value,
+                        root_field.definition,
+                        fields.as_ref(),
+                    );
+                }
+            }
+        }
+    }
+
+    validator
+        .finish(configuration.response.validation)
+        .map_err(|errors| {
+            MutationError::new_unprocessable_content(&errors[0])
+                .with_details(ResponseValidationError::details(&errors))
+        })
+}
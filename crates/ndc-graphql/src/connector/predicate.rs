@@ -0,0 +1,713 @@
+use common::{
+    comparison::ComparisonOperator,
+    config::{
+        config_file::ScalarRepresentation,
+        schema::{TypeDef, TypeRef},
+    },
+};
+use indexmap::IndexMap;
+use ndc_sdk::models::{self, FieldName, TypeName, VariableName};
+use serde_json::Value;
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display};
+
+#[derive(Debug)]
+pub enum PredicateError {
+    UnknownOperator(String),
+    ExpectedArray {
+        operator: ComparisonOperator,
+        value: Value,
+    },
+    MissingVariable(VariableName),
+    NotSupported(String),
+}
+
+impl std::error::Error for PredicateError {}
+
+impl Display for PredicateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PredicateError::UnknownOperator(operator) => {
+                write!(f, "Unknown comparison operator {operator}")
+            }
+            PredicateError::ExpectedArray { operator, value } => write!(
+                f,
+                "Comparison operator {operator} expects an array value, got {value}"
+            ),
+            PredicateError::MissingVariable(variable) => {
+                write!(f, "Missing variable {variable}")
+            }
+            PredicateError::NotSupported(feature) => {
+                write!(f, "Not supported in predicates: {feature}")
+            }
+        }
+    }
+}
+
+/// Evaluates predicates against rows of connector results.
+///
+/// Values are compared according to the scalar representation of the column they belong to,
+/// looked up in the schema by following the field path from the column's type. Integers and
+/// decimals compare exactly, whether sent as JSON numbers or strings, timestamps with a time zone
+/// compare by the instant they represent, and other strings compare lexicographically, which orders
+/// ISO 8601 dates and local timestamps correctly. Comparisons involving null are false, so rows with
+/// a null column are excluded by both `_eq` and `_neq`.
+pub struct PredicateEvaluator<'a> {
+    definitions: &'a BTreeMap<TypeName, TypeDef>,
+    /// the types of the columns of a row
+    columns: BTreeMap<FieldName, &'a TypeRef>,
+    variables: &'a BTreeMap<VariableName, Value>,
+}
+
+impl<'a> PredicateEvaluator<'a> {
+    pub fn new(
+        definitions: &'a BTreeMap<TypeName, TypeDef>,
+        columns: BTreeMap<FieldName, &'a TypeRef>,
+        variables: &'a BTreeMap<VariableName, Value>,
+    ) -> Self {
+        Self {
+            definitions,
+            columns,
+            variables,
+        }
+    }
+    pub fn evaluate(
+        &self,
+        expression: &models::Expression,
+        row: &IndexMap<FieldName, models::RowFieldValue>,
+    ) -> Result<bool, PredicateError> {
+        match expression {
+            models::Expression::And { expressions } => {
+                for expression in expressions {
+                    if !self.evaluate(expression, row)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            models::Expression::Or { expressions } => {
+                for expression in expressions {
+                    if self.evaluate(expression, row)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            models::Expression::Not { expression } => Ok(!self.evaluate(expression, row)?),
+            models::Expression::UnaryComparisonOperator { column, operator } => {
+                let (value, _) = self.column_value(column, row)?;
+                match operator {
+                    models::UnaryComparisonOperator::IsNull => Ok(value.is_null()),
+                }
+            }
+            models::Expression::BinaryComparisonOperator {
+                column,
+                operator,
+                value,
+            } => {
+                let operator: ComparisonOperator = operator
+                    .inner()
+                    .parse()
+                    .map_err(|_| PredicateError::UnknownOperator(operator.to_string()))?;
+                let (column, kind) = self.column_value(column, row)?;
+                let value = self.comparison_value(value)?;
+                compare(operator, kind, column, value)
+            }
+            models::Expression::ArrayComparison { column, comparison } => {
+                let (column, kind) = self.column_value(column, row)?;
+                let Value::Array(elements) = column else {
+                    return Ok(false);
+                };
+                match comparison {
+                    models::ArrayComparison::Contains { value } => {
+                        let value = self.comparison_value(value)?;
+                        Ok(elements
+                            .iter()
+                            .any(|element| values_equal(kind, element, value)))
+                    }
+                    models::ArrayComparison::IsEmpty => Ok(elements.is_empty()),
+                }
+            }
+            models::Expression::Exists { .. } => Err(PredicateError::NotSupported(
+                "exists expressions".to_string(),
+            )),
+        }
+    }
+    /// The value of the target column or nested field, and how to compare it
+    fn column_value<'r>(
+        &self,
+        column: &models::ComparisonTarget,
+        row: &'r IndexMap<FieldName, models::RowFieldValue>,
+    ) -> Result<(&'r Value, ValueKind), PredicateError> {
+        match column {
+            models::ComparisonTarget::Column {
+                name, field_path, ..
+            } => {
+                let field_path = field_path.as_deref().unwrap_or_default();
+                let kind = self.value_kind(name, field_path);
+                let Some(value) = row.get(name) else {
+                    return Ok((&Value::Null, kind));
+                };
+                let value = field_path
+                    .iter()
+                    .try_fold(&value.0, |value, field| value.get(field.inner().as_str()))
+                    .unwrap_or(&Value::Null);
+                Ok((value, kind))
+            }
+            models::ComparisonTarget::Aggregate { .. } => Err(PredicateError::NotSupported(
+                "comparisons against aggregates".to_string(),
+            )),
+        }
+    }
+    /// Follow the field path from the type of the column, to find the representation of the compared scalar
+    fn value_kind(&self, column: &FieldName, field_path: &[FieldName]) -> ValueKind {
+        let Some(column_type) = self.columns.get(column) else {
+            return ValueKind::Other;
+        };
+        let mut type_name = column_type.name();
+
+        for field in field_path {
+            match self.definitions.get(&type_name) {
+                Some(TypeDef::Object { fields, .. }) => match fields.get(field) {
+                    Some(field) => type_name = field.r#type.name(),
+                    None => return ValueKind::Other,
+                },
+                _ => return ValueKind::Other,
+            }
+        }
+
+        match self.definitions.get(&type_name) {
+            Some(TypeDef::Scalar { representation, .. }) => ValueKind::new(representation),
+            Some(TypeDef::Enum { .. }) => ValueKind::Text,
+            _ => ValueKind::Other,
+        }
+    }
+    fn comparison_value<'v>(
+        &'v self,
+        value: &'v models::ComparisonValue,
+    ) -> Result<&'v Value, PredicateError> {
+        match value {
+            models::ComparisonValue::Scalar { value } => Ok(value),
+            models::ComparisonValue::Variable { name } => self
+                .variables
+                .get(name)
+                .ok_or_else(|| PredicateError::MissingVariable(name.to_owned())),
+            models::ComparisonValue::Column { .. } => Err(PredicateError::NotSupported(
+                "comparisons against columns".to_string(),
+            )),
+        }
+    }
+}
+
+/// How values compare, by the scalar representation of the column they belong to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    /// integers and decimals of any size, sent as numbers or strings
+    Numeric,
+    /// RFC 3339 timestamps, which may have different offsets
+    TimestampTz,
+    /// uuids, which are case insensitive
+    Uuid,
+    /// strings ordered lexicographically, including dates and timestamps without a time zone
+    Text,
+    Boolean,
+    /// json, geo types, objects, and columns of unknown type, which only compare for equality
+    Other,
+}
+
+impl ValueKind {
+    fn new(representation: &ScalarRepresentation) -> Self {
+        match representation {
+            ScalarRepresentation::Int8
+            | ScalarRepresentation::Int16
+            | ScalarRepresentation::Int32
+            | ScalarRepresentation::Int64
+            | ScalarRepresentation::Float32
+            | ScalarRepresentation::Float64
+            | ScalarRepresentation::BigInteger
+            | ScalarRepresentation::BigDecimal => ValueKind::Numeric,
+            ScalarRepresentation::TimestampTz => ValueKind::TimestampTz,
+            ScalarRepresentation::Uuid => ValueKind::Uuid,
+            ScalarRepresentation::String
+            | ScalarRepresentation::Date
+            | ScalarRepresentation::Timestamp
+            | ScalarRepresentation::Bytes
+            | ScalarRepresentation::Enum { .. } => ValueKind::Text,
+            ScalarRepresentation::Boolean => ValueKind::Boolean,
+            ScalarRepresentation::Geography
+            | ScalarRepresentation::Geometry
+            | ScalarRepresentation::Json => ValueKind::Other,
+        }
+    }
+}
+
+fn compare(
+    operator: ComparisonOperator,
+    kind: ValueKind,
+    column: &Value,
+    value: &Value,
+) -> Result<bool, PredicateError> {
+    if column.is_null() {
+        return Ok(false);
+    }
+
+    let ordering = || compare_values(kind, column, value);
+
+    Ok(match operator {
+        ComparisonOperator::Eq => values_equal(kind, column, value),
+        ComparisonOperator::Neq => !value.is_null() && !values_equal(kind, column, value),
+        ComparisonOperator::Gt => ordering() == Some(Ordering::Greater),
+        ComparisonOperator::Gte => matches!(ordering(), Some(Ordering::Greater | Ordering::Equal)),
+        ComparisonOperator::Lt => ordering() == Some(Ordering::Less),
+        ComparisonOperator::Lte => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+        ComparisonOperator::In => match value {
+            Value::Array(values) => values.iter().any(|value| values_equal(kind, column, value)),
+            _ => {
+                return Err(PredicateError::ExpectedArray {
+                    operator,
+                    value: value.to_owned(),
+                })
+            }
+        },
+        ComparisonOperator::Like => match (column, value) {
+            (Value::String(column), Value::String(pattern)) => like(column, pattern),
+            _ => false,
+        },
+    })
+}
+
+fn values_equal(kind: ValueKind, left: &Value, right: &Value) -> bool {
+    if left.is_null() || right.is_null() {
+        return false;
+    }
+
+    match (kind, left, right) {
+        (ValueKind::Numeric | ValueKind::TimestampTz, _, _) => {
+            match compare_values(kind, left, right) {
+                Some(ordering) => ordering == Ordering::Equal,
+                None => left == right,
+            }
+        }
+        (ValueKind::Uuid, Value::String(left), Value::String(right)) => {
+            left.eq_ignore_ascii_case(right)
+        }
+        _ => left == right,
+    }
+}
+
+fn compare_values(kind: ValueKind, left: &Value, right: &Value) -> Option<Ordering> {
+    match (kind, left, right) {
+        (ValueKind::Numeric, _, _) => {
+            Decimal::from_value(left)?.partial_cmp(&Decimal::from_value(right)?)
+        }
+        (ValueKind::TimestampTz, Value::String(left), Value::String(right)) => {
+            instant(left)?.partial_cmp(&instant(right)?)
+        }
+        (ValueKind::Uuid, Value::String(left), Value::String(right)) => {
+            Some(left.to_lowercase().cmp(&right.to_lowercase()))
+        }
+        (ValueKind::Text, Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (ValueKind::Boolean, Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+/// A decimal number of any size and precision, as its sign, significant digits,
+/// and the exponent such that the value is `0.{digits} * 10^exponent`
+#[derive(Debug, PartialEq, Eq)]
+struct Decimal {
+    negative: bool,
+    /// without leading or trailing zeros, empty for zero
+    digits: String,
+    exponent: i64,
+}
+
+impl Decimal {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(number) => Self::parse(&number.to_string()),
+            Value::String(string) => Self::parse(string),
+            _ => None,
+        }
+    }
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+            None => (value, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        if (integer.is_empty() && fraction.is_empty())
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let digits = format!("{integer}{fraction}");
+        let significant = digits.trim_start_matches('0');
+        let leading_zeros = i64::try_from(digits.len() - significant.len()).ok()?;
+        let significant = significant.trim_end_matches('0');
+
+        if significant.is_empty() {
+            return Some(Self {
+                negative: false,
+                digits: String::new(),
+                exponent: 0,
+            });
+        }
+
+        Some(Self {
+            negative,
+            digits: significant.to_owned(),
+            // the exponent comes from the request, so a huge exponent makes the value invalid rather than overflowing
+            exponent: i64::try_from(integer.len())
+                .ok()?
+                .checked_add(exponent)?
+                .checked_sub(leading_zeros)?,
+        })
+    }
+    fn signum(&self) -> i8 {
+        match (self.digits.is_empty(), self.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let signum = self.signum();
+        let ordering = signum.cmp(&other.signum());
+        if ordering != Ordering::Equal || signum == 0 {
+            return ordering;
+        }
+
+        // digits without trailing zeros compare lexicographically once the exponents are equal
+        let magnitude = self
+            .exponent
+            .cmp(&other.exponent)
+            .then_with(|| self.digits.cmp(&other.digits));
+
+        if signum < 0 {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+/// The instant of an RFC 3339 timestamp, as seconds since the unix epoch and fractional seconds without trailing zeros
+fn instant(timestamp: &str) -> Option<(i64, &str)> {
+    let (date, time) = timestamp.split_once(['T', 't', ' '])?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: i64 = date_parts.next()?.parse().ok()?;
+    let day: i64 = date_parts.next()?.parse().ok()?;
+
+    let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        let index = time.rfind(['+', '-'])?;
+        let (hours, minutes) = time[index + 1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        let offset = if time[index..].starts_with('-') {
+            -offset
+        } else {
+            offset
+        };
+        (&time[..index], offset)
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+
+    let mut time_parts = time.splitn(3, ':');
+    let hours: i64 = time_parts.next()?.parse().ok()?;
+    let minutes: i64 = time_parts.next()?.parse().ok()?;
+    let seconds: i64 = time_parts.next()?.parse().ok()?;
+
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some((
+        days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds - offset,
+        fraction.trim_end_matches('0'),
+    ))
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // count years from March, so the leap day is at the end of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// SQL-style pattern matching: `%` matches any sequence of characters, `_` matches any single character
+fn like(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    // matches[j] is true if the value prefix processed so far matches the first j pattern characters
+    let mut matches = vec![false; pattern.len() + 1];
+    matches[0] = true;
+    for (j, p) in pattern.iter().enumerate() {
+        matches[j + 1] = matches[j] && *p == '%';
+    }
+
+    for c in &value {
+        let mut next = vec![false; pattern.len() + 1];
+        for (j, p) in pattern.iter().enumerate() {
+            next[j + 1] = match p {
+                '%' => next[j] || matches[j + 1],
+                '_' => matches[j],
+                p => matches[j] && p == c,
+            };
+        }
+        matches = next;
+    }
+
+    matches[pattern.len()]
+}
+
+#[test]
+fn test_evaluate_predicate() {
+    use common::config::{
+        schema::{parse_schema, SchemaDefinition},
+        SchemaConfig,
+    };
+
+    let schema = SchemaDefinition::new(
+        &parse_schema(
+            r"
+            type Query { user: User }
+            type User { name: String! age: Int! balance: BigDecimal! created: DateTime! nickname: String tags: [String!]! }
+            scalar BigDecimal scalar DateTime
+            ",
+        )
+        .unwrap(),
+        &SchemaConfig {
+            scalars: BTreeMap::from_iter([
+                ("BigDecimal".into(), ScalarRepresentation::BigDecimal),
+                ("DateTime".into(), ScalarRepresentation::TimestampTz),
+            ]),
+            ..SchemaConfig::default()
+        },
+    )
+    .unwrap();
+    let user_type = &schema.query_fields["user"].r#type;
+
+    let row: IndexMap<FieldName, models::RowFieldValue> =
+        serde_json::from_value(serde_json::json!({
+            "__value": {
+                "name": "Alice",
+                "age": 42,
+                "balance": "100000000000000000001.50",
+                "created": "2024-03-01T12:00:00+02:00",
+                "nickname": null,
+                "tags": ["admin", "staff"]
+            }
+        }))
+        .unwrap();
+    let variables = BTreeMap::from_iter([("min_age".into(), serde_json::json!(18))]);
+    let evaluator = PredicateEvaluator::new(
+        &schema.definitions,
+        BTreeMap::from_iter([("__value".into(), user_type)]),
+        &variables,
+    );
+
+    let comparison = |field: &str, operator: &str, value: serde_json::Value| serde_json::json!({ "type": "binary_comparison_operator", "column": { "type": "column", "name": "__value", "field_path": [field] }, "operator": operator, "value": { "type": "scalar", "value": value } });
+
+    let cases = [
+        (comparison("name", "_eq", "Alice".into()), true),
+        (comparison("name", "_neq", "Alice".into()), false),
+        (
+            serde_json::json!({ "type": "binary_comparison_operator", "column": { "type": "column", "name": "__value", "field_path": ["age"] }, "operator": "_gt", "value": { "type": "variable", "name": "min_age" } }),
+            true,
+        ),
+        (
+            comparison("age", "_in", serde_json::json!([41, 42.0])),
+            true,
+        ),
+        // decimals beyond the precision of floats compare exactly, whether sent as strings or numbers
+        (
+            comparison("balance", "_gt", "100000000000000000001".into()),
+            true,
+        ),
+        // timestamps compare by instant, regardless of their offset
+        (
+            comparison("created", "_lt", "2024-03-01T11:00:00Z".into()),
+            true,
+        ),
+        (comparison("name", "_like", "A_i%".into()), true),
+        (comparison("nickname", "_neq", "Al".into()), false),
+        (
+            serde_json::json!({ "type": "unary_comparison_operator", "column": { "type": "column", "name": "__value", "field_path": ["nickname"] }, "operator": "is_null" }),
+            true,
+        ),
+        (
+            serde_json::json!({ "type": "array_comparison", "column": { "type": "column", "name": "__value", "field_path": ["tags"] }, "comparison": { "type": "contains", "value": { "type": "scalar", "value": "staff" } } }),
+            true,
+        ),
+        (
+            serde_json::json!({ "type": "not", "expression": { "type": "or", "expressions": [] } }),
+            true,
+        ),
+        (
+            serde_json::json!({ "type": "and", "expressions": [
+            comparison("name", "_like", "%z%".into()),
+            { "type": "unary_comparison_operator", "column": { "type": "column", "name": "__value", "field_path": ["missing"] }, "operator": "is_null" }
+        ] }),
+            false,
+        ),
+    ];
+
+    for (expression, expected) in cases {
+        let predicate: models::Expression = serde_json::from_value(expression.clone()).unwrap();
+        assert_eq!(
+            evaluator.evaluate(&predicate, &row).unwrap(),
+            expected,
+            "{expression}"
+        );
+    }
+
+    let unknown_operator: models::Expression = serde_json::from_value(serde_json::json!({ "type": "binary_comparison_operator", "column": { "type": "column", "name": "__value" }, "operator": "_regex", "value": { "type": "scalar", "value": "." } })).unwrap();
+    assert!(matches!(
+        evaluator.evaluate(&unknown_operator, &row),
+        Err(PredicateError::UnknownOperator(_))
+    ));
+}
+
+#[test]
+fn test_compare_values() {
+    use serde_json::json;
+
+    let cases = [
+        (
+            ValueKind::Numeric,
+            json!(42),
+            json!("100"),
+            Some(Ordering::Less),
+        ),
+        (
+            ValueKind::Numeric,
+            json!("100000000000000000001.50"),
+            json!("1.000000000000000000015e20"),
+            Some(Ordering::Equal),
+        ),
+        (
+            ValueKind::Numeric,
+            json!("100000000000000000001.50"),
+            json!(1e21),
+            Some(Ordering::Less),
+        ),
+        (
+            ValueKind::Numeric,
+            json!("1.5"),
+            json!("-5"),
+            Some(Ordering::Greater),
+        ),
+        (ValueKind::Numeric, json!("1.5"), json!("one"), None),
+        (
+            ValueKind::TimestampTz,
+            json!("2024-03-01T12:00:00+02:00"),
+            json!("2024-03-01T10:00:00.000Z"),
+            Some(Ordering::Equal),
+        ),
+        (
+            ValueKind::TimestampTz,
+            json!("2024-03-01T12:00:00.5+02:00"),
+            json!("2024-03-01T10:00:00.25Z"),
+            Some(Ordering::Greater),
+        ),
+        (
+            ValueKind::TimestampTz,
+            json!("2024-03-01T12:00:00+02:00"),
+            json!("2024-02-29T23:59:59-12:00"),
+            Some(Ordering::Less),
+        ),
+        (
+            ValueKind::TimestampTz,
+            json!("2024-03-01T12:00:00Z"),
+            json!("2024-01-01"),
+            None,
+        ),
+        (
+            ValueKind::Uuid,
+            json!("7D3B4C2A-0000-4000-8000-000000000001"),
+            json!("7d3b4c2a-0000-4000-8000-000000000001"),
+            Some(Ordering::Equal),
+        ),
+        (
+            ValueKind::Text,
+            json!("2024-03-01"),
+            json!("2024-10-01"),
+            Some(Ordering::Less),
+        ),
+        (ValueKind::Other, json!({ "a": 1 }), json!({ "a": 2 }), None),
+    ];
+
+    for (kind, left, right, expected) in cases {
+        assert_eq!(
+            compare_values(kind, &left, &right),
+            expected,
+            "{left} {right}"
+        );
+        assert_eq!(
+            values_equal(kind, &left, &right),
+            expected == Some(Ordering::Equal),
+            "{left} {right}"
+        );
+    }
+}
+
+#[test]
+fn test_decimal_ordering() {
+    let ordered = [
+        "-1e3",
+        "-999.5",
+        "-1",
+        "-0.01",
+        "0",
+        "-0.0",
+        "1e-3",
+        "0.01",
+        "1",
+        "1.0",
+        "9.5",
+        "10",
+        "1e2",
+        "123456789012345678901234567890",
+    ];
+
+    for window in ordered.windows(2) {
+        let left = Decimal::parse(window[0]).unwrap();
+        let right = Decimal::parse(window[1]).unwrap();
+        assert!(left <= right, "{} <= {}", window[0], window[1]);
+    }
+    assert_eq!(Decimal::parse("1.0"), Decimal::parse("1"));
+    assert_eq!(Decimal::parse("-0.0"), Decimal::parse("0"));
+    assert_eq!(Decimal::parse("1.2.3"), None);
+    assert_eq!(Decimal::parse("abc"), None);
+    assert_eq!(Decimal::parse("1e9223372036854775807"), None);
+    assert_eq!(Decimal::parse("0.01e-9223372036854775808"), None);
+    assert!(Decimal::parse("1e9223372036854775806") > Decimal::parse("1e100"));
+}
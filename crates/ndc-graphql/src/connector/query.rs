@@ -1,8 +1,12 @@
-use super::state::ServerState;
+use super::{
+    predicate::PredicateEvaluator,
+    response_validation::{ResponseValidationError, ResponseValidator},
+    state::ServerState,
+};
 use crate::query_builder::build_query_document;
 use common::{
     client::{execute_graphql, GraphQLRequest},
-    config::ServerConfig,
+    config::{config_file::ResponseValidation, ServerConfig},
 };
 use indexmap::IndexMap;
 use ndc_sdk::{
@@ -50,6 +54,19 @@ pub async fn handle_query(
         tracing::event!(Level::DEBUG, "Incoming IR" = request_string);
     }
 
+    // predicates are evaluated against the single row produced for a request without variables
+    if request.query.predicate.is_some() && request.variables.is_some() {
+        return Err(QueryError::new_unsupported_operation(
+            &"Predicates are not supported in queries with variables",
+        ));
+    }
+    // forwarded headers wrap the row in an object the predicate columns do not describe
+    if request.query.predicate.is_some() && !configuration.response.forward_headers.is_empty() {
+        return Err(QueryError::new_unsupported_operation(
+            &"Predicates are not supported when forwarding response headers",
+        ));
+    }
+
     let operation = tracing::info_span!("Build Query Document", internal.visibility = "user")
         .in_scope(|| build_query_document(&request, configuration))?;
 
@@ -58,13 +75,19 @@ pub async fn handle_query(
         .await
         .map_err(|err| QueryError::new_invalid_request(&err))?;
 
+    let mut request_headers = operation.headers;
+    state
+        .authorize(&mut request_headers, &client, &configuration.connection)
+        .await
+        .map_err(|err| QueryError::new_invalid_request(&err))?;
+
     let execution_span = tracing::info_span!("Execute GraphQL Query", internal.visibility = "user");
 
     let (headers, response) = execute_graphql::<IndexMap<FieldName, models::RowFieldValue>>(
         &operation.query,
         operation.variables,
         &configuration.connection.endpoint,
-        &operation.headers,
+        &request_headers,
         &client,
         &configuration.response.forward_headers,
     )
@@ -76,7 +99,11 @@ pub async fn handle_query(
         if let Some(errors) = response.errors {
             Err(QueryError::new_unprocessable_content(&errors[0].message)
                 .with_details(serde_json::json!({ "errors": errors })))
-        } else if let Some(data) = response.data {
+        } else if let Some(mut data) = response.data {
+            if configuration.response.validation != ResponseValidation::Disabled {
+                validate_response(&mut data, &request, configuration)?;
+            }
+
             let forward_response_headers = !configuration.response.forward_headers.is_empty();
 
             let row = if forward_response_headers {
@@ -99,10 +126,12 @@ pub async fn handle_query(
                 data
             };
 
+            let rows = filter_rows(row, &request, configuration)?;
+
             Ok(models::QueryResponse(vec![models::RowSet {
                 groups: None,
                 aggregates: None,
-                rows: Some(vec![row]),
+                rows: Some(rows),
             }]))
         } else {
             Err(QueryError::new_unprocessable_content(
@@ -111,3 +140,107 @@ pub async fn handle_query(
         }
     })
 }
+
+/// Evaluate the predicate of the request, if any, against the single row of the response
+fn filter_rows(
+    row: IndexMap<FieldName, models::RowFieldValue>,
+    request: &models::QueryRequest,
+    configuration: &ServerConfig,
+) -> Result<Vec<IndexMap<FieldName, models::RowFieldValue>>, QueryError> {
+    let Some(predicate) = &request.query.predicate else {
+        return Ok(vec![row]);
+    };
+
+    let columns = configuration
+        .function_field(request.collection.as_str())
+        .map(|root_field| ("__value".into(), &root_field.definition.r#type))
+        .into_iter()
+        .collect();
+    let variables = BTreeMap::new();
+    let evaluator = PredicateEvaluator::new(&configuration.schema.definitions, columns, &variables);
+
+    if evaluator
+        .evaluate(predicate, &row)
+        .map_err(|err| QueryError::new_invalid_request(&err))?
+    {
+        Ok(vec![remove_predicate_fields(row, request)])
+    } else {
+        Ok(vec![])
+    }
+}
+
+/// Remove the fields that were only selected for evaluating the predicate
+fn remove_predicate_fields(
+    mut row: IndexMap<FieldName, models::RowFieldValue>,
+    request: &models::QueryRequest,
+) -> IndexMap<FieldName, models::RowFieldValue> {
+    let fields = match request
+        .query
+        .fields
+        .as_ref()
+        .and_then(|fields| fields.get("__value"))
+    {
+        Some(models::Field::Column { fields, .. }) => fields.as_ref(),
+        _ => None,
+    };
+
+    if let Some(value) = row.get_mut("__value") {
+        retain_selected_fields(&mut value.0, fields);
+    }
+
+    row
+}
+
+fn retain_selected_fields(value: &mut serde_json::Value, fields: Option<&models::NestedField>) {
+    match (value, fields) {
+        (serde_json::Value::Array(items), Some(models::NestedField::Array(array))) => {
+            for item in items {
+                retain_selected_fields(item, Some(&array.fields));
+            }
+        }
+        (serde_json::Value::Object(object), Some(models::NestedField::Object(selection))) => {
+            object.retain(|alias, _| selection.fields.contains_key(alias.as_str()));
+            for (alias, field) in &selection.fields {
+                if let (Some(value), models::Field::Column { fields, .. }) =
+                    (object.get_mut(alias.as_str()), field)
+                {
+                    retain_selected_fields(value, fields.as_ref());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_response(
+    data: &mut IndexMap<FieldName, models::RowFieldValue>,
+    request: &models::QueryRequest,
+    configuration: &ServerConfig,
+) -> Result<(), QueryError> {
+    let Some(root_field) = configuration.function_field(request.collection.as_str()) else {
+        return Ok(());
+    };
+    let fields = match request
+        .query
+        .fields
+        .as_ref()
+        .and_then(|fields| fields.get("__value"))
+    {
+        Some(models::Field::Column { fields, .. }) => fields.as_ref(),
+        _ => None,
+    };
+
+    let mut validator = ResponseValidator::new(&configuration.schema);
+
+    // with variables, each set of variables produces a root field aliased q{n}__value
+    for (alias, value) in data.iter_mut() {
+        validator.validate_root_field(alias.inner(), &mut value.0, root_field.definition, fields);
+    }
+
+    validator
+        .finish(configuration.response.validation)
+        .map_err(|errors| {
+            QueryError::new_unprocessable_content(&errors[0])
+                .with_details(ResponseValidationError::details(&errors))
+        })
+}
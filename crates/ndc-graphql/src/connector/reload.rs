@@ -0,0 +1,183 @@
+use common::config::{ConnectionConfig, SecretReloadConfig, ServerConfig};
+use prometheus::{IntCounter, Registry};
+use std::sync::Arc;
+use tokio::{sync::RwLock, task::JoinHandle, time::MissedTickBehavior};
+
+/// The configuration requests are handled with, which changes when reloaded connection settings differ
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<Arc<ServerConfig>>>);
+
+impl SharedConfig {
+    pub fn new(config: ServerConfig) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+    pub async fn get(&self) -> Arc<ServerConfig> {
+        self.0.read().await.clone()
+    }
+    /// Swap in a configuration with the new connection settings, returns false if they did not change
+    async fn set_connection(&self, connection: ConnectionConfig) -> bool {
+        let mut config = self.0.write().await;
+
+        if config.connection == connection {
+            return false;
+        }
+
+        let mut new_config = ServerConfig::clone(&config);
+        new_config.connection = connection;
+        *config = Arc::new(new_config);
+
+        true
+    }
+}
+
+/// Metrics for reloads of the connection settings
+#[derive(Debug, Clone)]
+pub struct SecretReloadMetrics {
+    reloads: IntCounter,
+    failed_reloads: IntCounter,
+}
+
+impl SecretReloadMetrics {
+    pub fn new(registry: &mut Registry) -> Result<Self, prometheus::Error> {
+        let reloads = IntCounter::new(
+            "ndc_graphql_secret_reloads_total",
+            "Successful reloads of the execution endpoint and headers",
+        )?;
+        let failed_reloads = IntCounter::new(
+            "ndc_graphql_secret_reload_failures_total",
+            "Reloads of the execution endpoint and headers that failed to resolve a value",
+        )?;
+
+        registry.register(Box::new(reloads.clone()))?;
+        registry.register(Box::new(failed_reloads.clone()))?;
+
+        Ok(Self {
+            reloads,
+            failed_reloads,
+        })
+    }
+}
+
+/// Background task resolving the connection settings again, aborted when dropped
+#[derive(Debug)]
+pub struct SecretReloadTask(JoinHandle<()>);
+
+impl SecretReloadTask {
+    pub fn spawn(
+        config: SharedConfig,
+        secret_reload: SecretReloadConfig,
+        metrics: SecretReloadMetrics,
+    ) -> Self {
+        Self(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(secret_reload.interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // the first tick completes immediately, the connection settings were just resolved
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                let connection = match secret_reload
+                    .connection
+                    .resolve(&secret_reload.environment, &secret_reload.configuration_dir)
+                {
+                    Ok(connection) => connection,
+                    Err(err) => {
+                        metrics.failed_reloads.inc();
+                        tracing::warn!(
+                            "Reloading connection settings failed, keeping the current settings: {err}"
+                        );
+                        continue;
+                    }
+                };
+
+                metrics.reloads.inc();
+
+                if config.set_connection(connection).await {
+                    tracing::info!("Reloaded connection settings changed, using the new settings");
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for SecretReloadTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_secret_reload() {
+    use super::setup::GraphQLConnectorSetup;
+    use ndc_sdk::connector::ConnectorSetup;
+    use prometheus::{Encoder, TextEncoder};
+    use std::{collections::HashMap, time::Duration};
+
+    let configuration_dir = std::env::temp_dir().join(format!(
+        "ndc-graphql-secret-reload-test-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&configuration_dir).unwrap();
+    std::fs::write(
+        configuration_dir.join("configuration.json"),
+        serde_json::json!({
+            "$schema": "configuration.schema.json",
+            "introspection": { "endpoint": { "value": "http://localhost/graphql" } },
+            "execution": {
+                "endpoint": { "valueFromEnv": "RELOAD_TEST_ENDPOINT" },
+                "headers": { "X-Secret": { "valueFromFile": "secret" } }
+            },
+            "secretReload": { "intervalSeconds": 1 }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        configuration_dir.join("schema.graphql"),
+        "type Query { hello: String }",
+    )
+    .unwrap();
+    std::fs::write(configuration_dir.join("secret"), "first\n").unwrap();
+
+    // the endpoint variable is only set in the setup's environment, not the process environment
+    let setup = GraphQLConnectorSetup::new(HashMap::from([(
+        "RELOAD_TEST_ENDPOINT".to_owned(),
+        "http://localhost/graphql".to_owned(),
+    )]));
+    let config = setup.read_configuration(&configuration_dir).await.unwrap();
+    let mut registry = Registry::new();
+    let state = setup.try_init_state(&config, &mut registry).await.unwrap();
+    assert_eq!(
+        state.configuration().await.connection.headers["X-Secret"],
+        "first"
+    );
+
+    std::fs::write(configuration_dir.join("secret"), "second\n").unwrap();
+
+    let mut header = String::new();
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        header.clone_from(&state.configuration().await.connection.headers["X-Secret"]);
+        if header == "second" {
+            break;
+        }
+    }
+    assert_eq!(header, "second");
+
+    let mut metrics = vec![];
+    TextEncoder::new()
+        .encode(&registry.gather(), &mut metrics)
+        .unwrap();
+    let metrics = String::from_utf8(metrics).unwrap();
+    assert!(
+        metrics.contains("\nndc_graphql_secret_reload_failures_total 0\n"),
+        "{metrics}"
+    );
+    assert!(
+        !metrics.contains("\nndc_graphql_secret_reloads_total 0\n"),
+        "{metrics}"
+    );
+
+    std::fs::remove_dir_all(&configuration_dir).unwrap();
+}
@@ -0,0 +1,306 @@
+use common::config::{
+    config_file::ResponseValidation,
+    schema::{ObjectFieldDefinition, SchemaDefinition, TypeDef, TypeRef},
+};
+use ndc_sdk::models::{self, NestedField};
+use std::fmt::Display;
+
+/// A value in the upstream response that does not match the schema
+#[derive(Debug)]
+pub enum ResponseValidationError {
+    UnexpectedNull {
+        path: String,
+        expected: String,
+    },
+    UnexpectedValue {
+        path: String,
+        expected: String,
+        value: serde_json::Value,
+    },
+    MissingField {
+        path: String,
+    },
+}
+
+impl ResponseValidationError {
+    /// details for the error response, when validation fails
+    pub fn details(errors: &[Self]) -> serde_json::Value {
+        serde_json::json!({
+            "errors": errors.iter().map(Self::to_json).collect::<Vec<_>>()
+        })
+    }
+    fn to_json(&self) -> serde_json::Value {
+        let path = match self {
+            ResponseValidationError::UnexpectedNull { path, .. }
+            | ResponseValidationError::UnexpectedValue { path, .. }
+            | ResponseValidationError::MissingField { path } => path,
+        };
+        serde_json::json!({ "path": path, "message": self.to_string() })
+    }
+}
+
+impl Display for ResponseValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponseValidationError::UnexpectedNull { path, expected } => {
+                write!(
+                    f,
+                    "Expected non-null value of type {expected} at {path}, got null"
+                )
+            }
+            ResponseValidationError::UnexpectedValue {
+                path,
+                expected,
+                value,
+            } => write!(
+                f,
+                "Expected value of type {expected} at {path}, got {value}"
+            ),
+            ResponseValidationError::MissingField { path } => {
+                write!(f, "Field {path} missing from response")
+            }
+        }
+    }
+}
+
+/// Marks a value that must be replaced with null, which propagates to the closest nullable parent.
+struct NullPropagation;
+
+/// Validates upstream responses against the schema, following the request's field selection.
+/// Invalid values are replaced with null following GraphQL null propagation rules,
+/// so the resulting response matches the schema advertised to the engine.
+pub struct ResponseValidator<'a> {
+    schema: &'a SchemaDefinition,
+    errors: Vec<ResponseValidationError>,
+}
+
+impl<'a> ResponseValidator<'a> {
+    pub fn new(schema: &'a SchemaDefinition) -> Self {
+        Self {
+            schema,
+            errors: vec![],
+        }
+    }
+    /// Report validation errors according to the configured validation mode.
+    /// Returns the errors if the response should be rejected.
+    pub fn finish(
+        self,
+        validation: ResponseValidation,
+    ) -> Result<(), Vec<ResponseValidationError>> {
+        match validation {
+            ResponseValidation::Error if !self.errors.is_empty() => Err(self.errors),
+            ResponseValidation::Disabled | ResponseValidation::Error => Ok(()),
+            ResponseValidation::Warn => {
+                for error in &self.errors {
+                    tracing::warn!("Invalid upstream response: {error}");
+                }
+                Ok(())
+            }
+        }
+    }
+    /// validate the value of a root field. If null propagates to the root field, it is set to null.
+    pub fn validate_root_field(
+        &mut self,
+        alias: &str,
+        value: &mut serde_json::Value,
+        field_definition: &ObjectFieldDefinition,
+        fields: Option<&NestedField>,
+    ) {
+        if self
+            .validate(alias, value, &field_definition.r#type, fields)
+            .is_err()
+        {
+            *value = serde_json::Value::Null;
+        }
+    }
+    fn validate(
+        &mut self,
+        path: &str,
+        value: &mut serde_json::Value,
+        r#type: &TypeRef,
+        fields: Option<&NestedField>,
+    ) -> Result<(), NullPropagation> {
+        if let TypeRef::NonNull(underlying) = r#type {
+            if value.is_null() {
+                self.errors.push(ResponseValidationError::UnexpectedNull {
+                    path: path.to_owned(),
+                    expected: r#type.to_string(),
+                });
+                return Err(NullPropagation);
+            }
+            self.validate_non_null(path, value, underlying, fields)
+        } else {
+            if !value.is_null() && self.validate_non_null(path, value, r#type, fields).is_err() {
+                *value = serde_json::Value::Null;
+            }
+            Ok(())
+        }
+    }
+    fn validate_non_null(
+        &mut self,
+        path: &str,
+        value: &mut serde_json::Value,
+        r#type: &TypeRef,
+        fields: Option<&NestedField>,
+    ) -> Result<(), NullPropagation> {
+        match r#type {
+            TypeRef::NonNull(underlying) => self.validate_non_null(path, value, underlying, fields),
+            TypeRef::List(underlying) => {
+                let serde_json::Value::Array(items) = value else {
+                    return Err(self.unexpected_value(path, r#type, value));
+                };
+                let fields = match fields {
+                    Some(NestedField::Array(array)) => Some(array.fields.as_ref()),
+                    fields => fields,
+                };
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.validate(&format!("{path}[{index}]"), item, underlying, fields)?;
+                }
+                Ok(())
+            }
+            TypeRef::Named(name) => match self.schema.definitions.get(name.as_str()) {
+                Some(TypeDef::Scalar { .. }) => {
+                    if scalar_value_matches(name, value) {
+                        Ok(())
+                    } else {
+                        Err(self.unexpected_value(path, r#type, value))
+                    }
+                }
+                Some(TypeDef::Enum { values, .. }) => match value {
+                    serde_json::Value::String(value)
+                        if values.iter().any(|enum_value| &enum_value.name == value) =>
+                    {
+                        Ok(())
+                    }
+                    _ => Err(self.unexpected_value(path, r#type, value)),
+                },
+                Some(TypeDef::Object {
+                    fields: field_definitions,
+                    ..
+                }) => {
+                    let serde_json::Value::Object(object) = value else {
+                        return Err(self.unexpected_value(path, r#type, value));
+                    };
+                    let Some(NestedField::Object(selection)) = fields else {
+                        return Ok(());
+                    };
+
+                    for (alias, field) in &selection.fields {
+                        let models::Field::Column { column, fields, .. } = field else {
+                            continue;
+                        };
+                        let Some(field_definition) = field_definitions.get(column) else {
+                            continue;
+                        };
+                        let field_path = format!("{path}.{alias}");
+
+                        if let Some(field_value) = object.get_mut(alias.as_str()) {
+                            self.validate(
+                                &field_path,
+                                field_value,
+                                &field_definition.r#type,
+                                fields.as_ref(),
+                            )?;
+                        } else {
+                            self.errors.push(ResponseValidationError::MissingField {
+                                path: field_path.clone(),
+                            });
+                            // a missing field is treated as null
+                            let mut field_value = serde_json::Value::Null;
+                            self.validate(
+                                &field_path,
+                                &mut field_value,
+                                &field_definition.r#type,
+                                fields.as_ref(),
+                            )?;
+                            object.insert(alias.to_string(), field_value);
+                        }
+                    }
+                    Ok(())
+                }
+                // input objects never appear in responses, and unknown types cannot be checked
+                Some(TypeDef::InputObject { .. }) | None => Ok(()),
+            },
+        }
+    }
+    fn unexpected_value(
+        &mut self,
+        path: &str,
+        r#type: &TypeRef,
+        value: &serde_json::Value,
+    ) -> NullPropagation {
+        self.errors.push(ResponseValidationError::UnexpectedValue {
+            path: path.to_owned(),
+            expected: r#type.to_string(),
+            value: value.clone(),
+        });
+        NullPropagation
+    }
+}
+
+/// Built-in scalars have a known shape. Custom scalars may be any JSON value.
+fn scalar_value_matches(name: &str, value: &serde_json::Value) -> bool {
+    match name {
+        "Int" => value
+            .as_i64()
+            .is_some_and(|value| i32::try_from(value).is_ok()),
+        "Float" => value.is_number(),
+        "String" => value.is_string(),
+        "Boolean" => value.is_boolean(),
+        "ID" => value.is_string() || value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+#[test]
+fn test_null_propagation() {
+    use common::config::{schema::parse_schema, SchemaConfig};
+
+    let schema_document = parse_schema(
+        r"
+        type Query { items: [Item!], item: Item }
+        type Item { id: Int!, name: String }
+        ",
+    )
+    .expect("schema should parse");
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect("schema should be valid");
+
+    let fields: NestedField = serde_json::from_value(serde_json::json!({
+        "type": "object",
+        "fields": {
+            "id": { "type": "column", "column": "id" },
+            "name": { "type": "column", "column": "name" }
+        }
+    }))
+    .expect("fields should deserialize");
+
+    let mut validator = ResponseValidator::new(&schema);
+
+    let mut item = serde_json::json!({ "id": 1, "name": 5 });
+    validator.validate_root_field(
+        "item",
+        &mut item,
+        &schema.query_fields["item"],
+        Some(&fields),
+    );
+    assert_eq!(item, serde_json::json!({ "id": 1, "name": null }));
+
+    let mut items = serde_json::json!([{ "id": 1, "name": "a" }, { "id": null, "name": "b" }]);
+    validator.validate_root_field(
+        "items",
+        &mut items,
+        &schema.query_fields["items"],
+        Some(&fields),
+    );
+    assert_eq!(items, serde_json::Value::Null);
+
+    let errors: Vec<String> = validator.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "Expected value of type String at item.name, got 5",
+            "Expected non-null value of type Int! at items[1].id, got null",
+        ]
+    );
+}
@@ -1,11 +1,12 @@
 use super::{state::ServerState, GraphQLConnector};
 use async_trait::async_trait;
 use common::config::{
-    config_file::{ConfigValue, ServerConfigFile, CONFIG_FILE_NAME, SCHEMA_FILE_NAME},
-    schema::SchemaDefinition,
-    ConnectionConfig, ServerConfig,
+    config_file::{
+        ConfigValue, ConnectionConfigFile, ServerConfigFile, CONFIG_FILE_NAME, SCHEMA_FILE_NAME,
+    },
+    schema::{parse_schema, LocatedSchemaDefinitionError, RootTypesSource, SchemaDefinition},
+    ConnectionConfig, OAuth2Config, SchemaConfig, SecretReloadConfig, ServerConfig,
 };
-use graphql_parser::parse_schema;
 use ndc_sdk::connector::{
     self, Connector, ConnectorSetup, InvalidNode, InvalidNodes, KeyOrIndex, LocatedError,
     ParseError,
@@ -41,9 +42,9 @@ impl ConnectorSetup for GraphQLConnectorSetup {
     async fn try_init_state(
         &self,
         configuration: &<Self::Connector as Connector>::Configuration,
-        _metrics: &mut prometheus::Registry,
+        metrics: &mut prometheus::Registry,
     ) -> connector::Result<<Self::Connector as Connector>::State> {
-        Ok(ServerState::new(configuration))
+        Ok(ServerState::new(configuration, metrics)?)
     }
 }
 
@@ -83,75 +84,171 @@ impl GraphQLConnectorSetup {
 
         let schema_document = parse_schema(&schema_string).map_err(|err| {
             ParseError::ParseError(LocatedError {
-                file_path: config_file_path.clone(),
-                line: 0,
-                column: 0,
-                message: err.to_string(),
+                file_path: schema_file_path.clone(),
+                line: err.position.map_or(0, |position| position.line),
+                column: err.position.map_or(0, |position| position.column),
+                message: err.message,
             })
         })?;
 
-        let request_config = config_file.request.unwrap_or_default().into();
-        let response_config = config_file.response.unwrap_or_default().into();
+        let schema_config = SchemaConfig::from(&config_file);
+
+        // collect all problems, so they can be reported at once
+        let mut invalid_nodes = vec![];
+
+        let schema = SchemaDefinition::new(&schema_document, &schema_config)
+            .map_err(|errors| {
+                invalid_nodes.extend(errors.0.into_iter().map(|error| {
+                    invalid_schema_node(&config_file_path, &schema_file_path, &error)
+                }));
+            })
+            .ok();
+
+        let connection = self.read_execution_connection(
+            configuration_dir.as_ref(),
+            &config_file_path,
+            &config_file.execution,
+            &mut invalid_nodes,
+        );
 
-        let schema = SchemaDefinition::new(&schema_document, &request_config, &response_config)
-            .map_err(|err| {
-                ParseError::ValidateError(InvalidNodes(vec![InvalidNode {
-                    file_path: schema_file_path,
-                    node_path: vec![],
-                    message: err.to_string(),
-                }]))
-            })?;
+        let (schema, connection) = match (schema, connection) {
+            (Some(schema), Some(connection)) if invalid_nodes.is_empty() => (schema, connection),
+            _ => return Err(ParseError::ValidateError(InvalidNodes(invalid_nodes))),
+        };
+
+        if schema.root_types_source == RootTypesSource::DefaultNames {
+            tracing::info!("{}", schema.describe_root_types());
+        }
+        if let Some(unsupported_types) = schema.describe_unsupported_types() {
+            tracing::warn!("{unsupported_types}");
+        }
 
         let config = ServerConfig {
             schema,
-            connection: ConnectionConfig {
-                endpoint: self.read_config_value(
-                    &config_file_path,
-                    &["connection", "endpoint"],
-                    config_file.execution.endpoint,
-                )?,
-                headers: config_file
-                    .execution
-                    .headers
-                    .into_iter()
-                    .map(|(header_name, header_value)| {
-                        let value = self.read_config_value(
-                            &config_file_path,
-                            &["connection", "headers", &header_name, "value"],
-                            header_value,
-                        )?;
-                        Ok((header_name, value))
-                    })
-                    .collect::<Result<_, ParseError>>()?,
-            },
-            request: request_config,
-            response: response_config,
+            connection,
+            request: schema_config.request,
+            response: schema_config.response,
+            naming: schema_config.naming,
+            scalars: schema_config.scalars,
+            filter: schema_config.filter,
+            operations: schema_config.operations,
+            drift_detection: config_file.drift_detection.map(Into::into),
+            secret_reload: config_file.secret_reload.map(|secret_reload| {
+                SecretReloadConfig::new(
+                    secret_reload,
+                    config_file.execution,
+                    configuration_dir.as_ref().to_owned(),
+                    self.environment.clone(),
+                )
+            }),
         };
 
         Ok(config)
     }
 
+    /// Resolve the execution endpoint, headers and OAuth2 settings, collecting every value that could not be resolved
+    fn read_execution_connection(
+        &self,
+        configuration_dir: &Path,
+        file_path: &PathBuf,
+        connection: &ConnectionConfigFile,
+        invalid_nodes: &mut Vec<InvalidNode>,
+    ) -> Option<ConnectionConfig> {
+        let endpoint = self
+            .read_config_value(
+                configuration_dir,
+                file_path,
+                &["execution", "endpoint"],
+                &connection.endpoint,
+            )
+            .map_err(|invalid_node| invalid_nodes.push(invalid_node))
+            .ok();
+
+        let headers = connection
+            .headers
+            .iter()
+            .filter_map(|(header_name, header_value)| {
+                let value = self
+                    .read_config_value(
+                        configuration_dir,
+                        file_path,
+                        &["execution", "headers", header_name],
+                        header_value,
+                    )
+                    .map_err(|invalid_node| invalid_nodes.push(invalid_node))
+                    .ok()?;
+                Some((header_name.to_owned(), value))
+            })
+            .collect();
+
+        let oauth2 = match &connection.oauth2 {
+            Some(oauth2) => {
+                let [token_url, client_id, client_secret] = oauth2.values().map(|(key, value)| {
+                    self.read_config_value(
+                        configuration_dir,
+                        file_path,
+                        &["execution", "oauth2", key],
+                        value,
+                    )
+                    .map_err(|invalid_node| invalid_nodes.push(invalid_node))
+                    .ok()
+                });
+                Some(OAuth2Config {
+                    token_url: token_url?,
+                    client_id: client_id?,
+                    client_secret: client_secret?,
+                    scopes: oauth2.scopes.clone().unwrap_or_default(),
+                    audience: oauth2.audience.clone(),
+                })
+            }
+            None => None,
+        };
+
+        Some(ConnectionConfig {
+            endpoint: endpoint?,
+            headers,
+            oauth2,
+        })
+    }
+
     fn read_config_value(
         &self,
+        configuration_dir: &Path,
         file_path: &PathBuf,
         node_path: &[&str],
-        value: ConfigValue,
-    ) -> Result<String, ParseError> {
-        match value {
-            ConfigValue::Value(v) => Ok(v),
-            ConfigValue::ValueFromEnv(e) => {
-                Ok(self.environment.get(&e).cloned().ok_or_else(|| {
-                    ParseError::ValidateError(InvalidNodes(vec![InvalidNode {
-                        file_path: file_path.to_owned(),
-                        node_path: node_path
-                            .iter()
-                            .map(|s| KeyOrIndex::Key((*s).to_owned()))
-                            .chain(once(KeyOrIndex::Key("valueFromEnv".to_owned())))
-                            .collect(),
-                        message: format!("Environment Variable {e} not set"),
-                    }]))
-                })?)
-            }
-        }
+        value: &ConfigValue,
+    ) -> Result<String, InvalidNode> {
+        value
+            .resolve(&self.environment, configuration_dir)
+            .map_err(|err| InvalidNode {
+                file_path: file_path.to_owned(),
+                node_path: node_path
+                    .iter()
+                    .map(|s| KeyOrIndex::Key((*s).to_owned()))
+                    .chain(once(KeyOrIndex::Key(value.key().to_owned())))
+                    .collect(),
+                message: err.to_string(),
+            })
+    }
+}
+
+/// Schema errors that can be resolved by changing the configuration file are reported against it,
+/// all others are reported against the schema file.
+fn invalid_schema_node(
+    config_file_path: &Path,
+    schema_file_path: &Path,
+    error: &LocatedSchemaDefinitionError,
+) -> InvalidNode {
+    match error.error.config_node_path() {
+        Some(node_path) => InvalidNode {
+            file_path: config_file_path.to_owned(),
+            node_path: node_path.into_iter().map(KeyOrIndex::Key).collect(),
+            message: error.to_string(),
+        },
+        None => InvalidNode {
+            file_path: schema_file_path.to_owned(),
+            node_path: vec![],
+            message: error.to_string(),
+        },
     }
 }
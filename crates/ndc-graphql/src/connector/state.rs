@@ -1,21 +1,78 @@
-use std::{error::Error, sync::Arc};
+use std::{collections::BTreeMap, error::Error, sync::Arc};
 
-use common::{client::get_http_client, config::ServerConfig};
+use common::{
+    client::get_http_client,
+    config::{ConnectionConfig, ServerConfig},
+    oauth2::{OAuth2Error, TokenCache},
+};
 use tokio::sync::RwLock;
 
+use super::{
+    drift::{SchemaDriftMetrics, SchemaDriftTask},
+    reload::{SecretReloadMetrics, SecretReloadTask, SharedConfig},
+};
+
 #[derive(Debug, Clone)]
 pub struct ServerState {
     client: Arc<RwLock<Option<reqwest::Client>>>,
+    configuration: SharedConfig,
+    token_cache: Arc<TokenCache>,
+    /// set if drift detection is configured, the task stops when the last copy of the state is dropped
+    _schema_drift: Option<Arc<SchemaDriftTask>>,
+    /// set if secret reload is configured, the task stops when the last copy of the state is dropped
+    _secret_reload: Option<Arc<SecretReloadTask>>,
 }
 
 impl ServerState {
-    pub fn new(config: &ServerConfig) -> ServerState {
+    pub fn new(
+        config: &ServerConfig,
+        metrics: &mut prometheus::Registry,
+    ) -> Result<ServerState, prometheus::Error> {
         // if client creation fails for whatever reason, client should be none.
         let client = get_http_client(&config.connection).ok();
 
-        ServerState {
+        let configuration = SharedConfig::new(config.clone());
+
+        let schema_drift = match &config.drift_detection {
+            Some(drift_detection) => Some(Arc::new(SchemaDriftTask::spawn(
+                configuration.clone(),
+                drift_detection.interval,
+                SchemaDriftMetrics::new(metrics)?,
+            ))),
+            None => None,
+        };
+
+        let secret_reload = match &config.secret_reload {
+            Some(secret_reload) => Some(Arc::new(SecretReloadTask::spawn(
+                configuration.clone(),
+                secret_reload.clone(),
+                SecretReloadMetrics::new(metrics)?,
+            ))),
+            None => None,
+        };
+
+        Ok(ServerState {
             client: Arc::new(RwLock::new(client)),
-        }
+            configuration,
+            token_cache: Arc::new(TokenCache::default()),
+            _schema_drift: schema_drift,
+            _secret_reload: secret_reload,
+        })
+    }
+    /// The configuration to handle requests with, with the latest reloaded connection settings
+    pub async fn configuration(&self) -> Arc<ServerConfig> {
+        self.configuration.get().await
+    }
+    /// Set the Authorization header to a cached OAuth2 access token, if the connection uses OAuth2
+    pub async fn authorize(
+        &self,
+        headers: &mut BTreeMap<String, String>,
+        client: &reqwest::Client,
+        connection: &ConnectionConfig,
+    ) -> Result<(), OAuth2Error> {
+        self.token_cache
+            .authorize(headers, client, connection)
+            .await
     }
     pub async fn client(&self, config: &ServerConfig) -> Result<reqwest::Client, Box<dyn Error>> {
         if let Some(client) = &*self.client.read().await {
@@ -1,13 +1,16 @@
-use self::{error::QueryBuilderError, operation_parameters::OperationParameters};
+use self::{
+    argument_validation::ArgumentValidator, error::QueryBuilderError,
+    operation_parameters::OperationParameters,
+};
 use common::config::{
-    schema::{ObjectFieldDefinition, TypeDef},
-    ServerConfig,
+    schema::{ObjectFieldDefinition, OperationType, TypeDef, TypeRef},
+    RootField, ServerConfig,
 };
 use glob_match::glob_match;
 use graphql_parser::{
     query::{
         Definition, Document, Field, Mutation, OperationDefinition, Query, Selection, SelectionSet,
-        Value,
+        Value, VariableDefinition,
     },
     Pos,
 };
@@ -17,6 +20,7 @@ use ndc_sdk::models::{
 };
 use std::collections::BTreeMap;
 
+mod argument_validation;
 pub mod error;
 mod operation_parameters;
 
@@ -36,16 +40,13 @@ pub fn build_mutation_document(
 ) -> Result<Operation, QueryBuilderError> {
     // mutations don't have variables, so we use an empty set
     let dummy_variables = BTreeMap::new();
-    let mut parameters = OperationParameters::new("");
+    let mut builder = FieldBuilder::new(configuration, &dummy_variables, "");
 
     let mut request_headers = BTreeMap::new();
     let mut items = vec![];
 
-    let mutation_type_name = configuration
-        .schema
-        .mutation_type_name
-        .as_ref()
-        .ok_or(QueryBuilderError::NoMutationType)?;
+    // procedures may target query fields, but query and mutation fields can't share an operation
+    let mut operation_type = None;
 
     for (index, operation) in request.operations.iter().enumerate() {
         match operation {
@@ -54,19 +55,25 @@ pub fn build_mutation_document(
                 arguments,
                 fields,
             } => {
-                let field_name: FieldName = name.to_string().into();
                 let alias = format!("procedure_{index}");
-                let field_definition =
-                    configuration
-                        .schema
-                        .mutation_fields
-                        .get(name)
-                        .ok_or_else(|| QueryBuilderError::MutationFieldNotFound {
-                            field: name.to_owned(),
-                        })?;
+                let root_field = configuration
+                    .procedure_field(name.as_str())
+                    .ok_or_else(|| QueryBuilderError::MutationFieldNotFound {
+                        field: name.to_owned(),
+                    })?;
+
+                if operation_type
+                    .is_some_and(|operation_type| operation_type != root_field.operation_type)
+                {
+                    return Err(QueryBuilderError::NotSupported(
+                        "Procedures for both query and mutation fields in a single request"
+                            .to_string(),
+                    ));
+                }
+                operation_type = Some(root_field.operation_type);
 
                 let (headers, procedure_arguments) =
-                    extract_headers(arguments, map_arg, configuration, &BTreeMap::new())?;
+                    extract_headers(arguments.clone(), configuration)?;
 
                 // note: duplicate headers get dropped here
                 // if there are multiple root fields, preset headers get set here once per field,
@@ -74,23 +81,11 @@ pub fn build_mutation_document(
                 // this should not matter as headers should be identical anyways
                 request_headers.extend(headers.into_iter());
 
-                let item = selection_set_field(
+                let item = builder.root_field(
                     &alias,
-                    &field_name,
-                    field_arguments(
-                        &procedure_arguments,
-                        map_arg,
-                        field_definition,
-                        &mut parameters,
-                        &field_name,
-                        mutation_type_name,
-                        &dummy_variables,
-                    )?,
-                    fields,
-                    field_definition,
-                    &mut parameters,
-                    configuration,
-                    &dummy_variables,
+                    &root_field,
+                    &procedure_arguments,
+                    fields.as_ref(),
                 )?;
 
                 items.push(item);
@@ -108,17 +103,13 @@ pub fn build_mutation_document(
         items,
     };
 
-    let (values, variable_definitions) = parameters.into_parameter_definitions();
+    let (values, variable_definitions) = builder.parameters.into_parameter_definitions();
 
     let document: Document<String> = Document {
-        definitions: vec![Definition::Operation(OperationDefinition::Mutation(
-            Mutation {
-                position: pos(),
-                name: None,
-                variable_definitions,
-                directives: vec![],
-                selection_set,
-            },
+        definitions: vec![Definition::Operation(operation_definition(
+            operation_type.unwrap_or(OperationType::Mutation),
+            variable_definitions,
+            selection_set,
         ))],
     };
 
@@ -133,12 +124,6 @@ pub fn build_query_document(
     request: &models::QueryRequest,
     configuration: &ServerConfig,
 ) -> Result<Operation, QueryBuilderError> {
-    let query_type_name = configuration
-        .schema
-        .query_type_name
-        .as_ref()
-        .ok_or(QueryBuilderError::NoQueryType)?;
-
     let root_field = request
         .query
         .fields
@@ -171,93 +156,62 @@ pub fn build_query_document(
         ));
     }
 
-    let root_field_definition = configuration
-        .schema
-        .query_fields
-        .get(&request.collection)
+    let root_field = configuration
+        .function_field(request.collection.as_str())
         .ok_or_else(|| QueryBuilderError::QueryFieldNotFound {
             field: request.collection.clone(),
         })?;
 
-    let (mut headers, items, variable_values, variable_definitions) =
-        if let Some(variables) = &request.variables {
-            let mut variable_values = BTreeMap::new();
-            let mut variable_definitions = vec![];
-            let mut items = vec![];
-            let mut all_headers = BTreeMap::new();
-
-            for (index, variables) in variables.iter().enumerate() {
-                let mut parameters = OperationParameters::new(format!("q{}_", index + 1));
-
-                let (mut headers, request_arguments) =
-                    extract_headers(&request.arguments, map_query_arg, configuration, variables)?;
-
-                // note: all_headers is a BTreeMap. Duplicate headers will be discarded here, and the last one will be used
-                all_headers.append(&mut headers);
-
-                let item = selection_set_field(
-                    &format!("q{}__value", index + 1),
-                    &request.collection.to_string().into(),
-                    field_arguments(
-                        &request_arguments,
-                        map_arg,
-                        root_field_definition,
-                        &mut parameters,
-                        &request.collection.to_string().into(),
-                        query_type_name,
+    // predicates are evaluated against the response, so the fields they compare must be selected
+    let subfields = match &request.query.predicate {
+        Some(predicate) => select_predicate_fields(subfields.clone(), predicate)?,
+        None => subfields.clone(),
+    };
+
+    // if the query does not have variables, we use an empty set
+    let dummy_variables = BTreeMap::new();
+    // with variables, each set gets its own root field and parameter namespace
+    let variable_sets: Vec<(String, String, &BTreeMap<VariableName, serde_json::Value>)> =
+        match &request.variables {
+            Some(variables) => variables
+                .iter()
+                .enumerate()
+                .map(|(index, variables)| {
+                    (
+                        format!("q{}__value", index + 1),
+                        format!("q{}_", index + 1),
                         variables,
-                    )?,
-                    subfields,
-                    root_field_definition,
-                    &mut parameters,
-                    configuration,
-                    variables,
-                )?;
+                    )
+                })
+                .collect(),
+            None => vec![("__value".to_owned(), String::new(), &dummy_variables)],
+        };
 
-                let (mut values, mut definitions) = parameters.into_parameter_definitions();
+    let mut headers = BTreeMap::new();
+    let mut items = vec![];
+    let mut variable_values = BTreeMap::new();
+    let mut variable_definitions = vec![];
 
-                items.push(item);
+    for (alias, namespace, variables) in variable_sets {
+        let (mut field_headers, request_arguments) = extract_headers(
+            map_query_arguments(&request.arguments, variables)?,
+            configuration,
+        )?;
 
-                variable_values.append(&mut values);
-                variable_definitions.append(&mut definitions);
-            }
+        // note: headers is a BTreeMap. Duplicate headers will be discarded here, and the last one will be used
+        headers.append(&mut field_headers);
 
-            (all_headers, items, variable_values, variable_definitions)
-        } else {
-            let mut parameters = OperationParameters::new("");
-            // if the query does not have variables, we use an empty set
-            let dummy_variables = BTreeMap::new();
-
-            let (headers, request_arguments) = extract_headers(
-                &request.arguments,
-                map_query_arg,
-                configuration,
-                &dummy_variables,
-            )?;
+        let mut builder = FieldBuilder::new(configuration, variables, namespace);
+        let item =
+            builder.root_field(&alias, &root_field, &request_arguments, subfields.as_ref())?;
 
-            let item = selection_set_field(
-                "__value",
-                &request.collection.to_string().into(),
-                field_arguments(
-                    &request_arguments,
-                    map_arg,
-                    root_field_definition,
-                    &mut parameters,
-                    &request.collection.to_string().into(),
-                    query_type_name,
-                    &dummy_variables,
-                )?,
-                subfields,
-                root_field_definition,
-                &mut parameters,
-                configuration,
-                &dummy_variables,
-            )?;
+        let (mut values, mut definitions) = builder.parameters.into_parameter_definitions();
 
-            let (variable_values, variable_definitions) = parameters.into_parameter_definitions();
+        items.push(item);
 
-            (headers, vec![item], variable_values, variable_definitions)
-        };
+        variable_values.append(&mut values);
+        variable_definitions.append(&mut definitions);
+    }
 
     let mut request_level_headers =
         extract_headers_from_request_arguments(request.request_arguments.as_ref())?;
@@ -270,13 +224,11 @@ pub fn build_query_document(
     };
 
     let document = Document {
-        definitions: vec![Definition::Operation(OperationDefinition::Query(Query {
-            position: pos(),
-            name: None,
+        definitions: vec![Definition::Operation(operation_definition(
+            root_field.operation_type,
             variable_definitions,
-            directives: vec![],
             selection_set,
-        }))],
+        ))],
     };
 
     Ok(Operation {
@@ -286,32 +238,63 @@ pub fn build_query_document(
     })
 }
 
+fn root_type_name(
+    operation_type: OperationType,
+    configuration: &ServerConfig,
+) -> Result<&TypeName, QueryBuilderError> {
+    match operation_type {
+        OperationType::Query => configuration
+            .schema
+            .query_type_name
+            .as_ref()
+            .ok_or(QueryBuilderError::NoQueryType),
+        OperationType::Mutation => configuration
+            .schema
+            .mutation_type_name
+            .as_ref()
+            .ok_or(QueryBuilderError::NoMutationType),
+    }
+}
+
+fn operation_definition<'a>(
+    operation_type: OperationType,
+    variable_definitions: Vec<VariableDefinition<'a, String>>,
+    selection_set: SelectionSet<'a, String>,
+) -> OperationDefinition<'a, String> {
+    match operation_type {
+        OperationType::Query => OperationDefinition::Query(Query {
+            position: pos(),
+            name: None,
+            variable_definitions,
+            directives: vec![],
+            selection_set,
+        }),
+        OperationType::Mutation => OperationDefinition::Mutation(Mutation {
+            position: pos(),
+            name: None,
+            variable_definitions,
+            directives: vec![],
+            selection_set,
+        }),
+    }
+}
+
 type Headers = BTreeMap<String, String>;
 type Arguments = BTreeMap<ArgumentName, serde_json::Value>;
 
 /// extract the headers argument if present and applicable
 /// returns the headers for this request, including base headers and forwarded headers
-fn extract_headers<A, M>(
-    arguments: &BTreeMap<ArgumentName, A>,
-    map_argument: M,
+fn extract_headers(
+    arguments: Arguments,
     configuration: &ServerConfig,
-    variables: &BTreeMap<VariableName, serde_json::Value>,
-) -> Result<(Headers, Arguments), QueryBuilderError>
-where
-    M: Fn(
-        &A,
-        &BTreeMap<VariableName, serde_json::Value>,
-    ) -> Result<serde_json::Value, QueryBuilderError>,
-{
+) -> Result<(Headers, Arguments), QueryBuilderError> {
     let mut request_arguments = BTreeMap::new();
     let mut headers = configuration.connection.headers.clone();
 
     let patterns = &configuration.request.forward_headers;
 
-    for (name, argument) in arguments {
-        let value = map_argument(argument, variables)?;
-
-        if name == &configuration.request.headers_argument {
+    for (name, value) in arguments {
+        if name == configuration.request.headers_argument {
             match value {
                 serde_json::Value::Null
                 | serde_json::Value::Bool(_)
@@ -345,161 +328,208 @@ where
                 }
             }
         } else {
-            request_arguments.insert(name.to_owned(), value);
+            request_arguments.insert(name, value);
         }
     }
 
     Ok((headers, request_arguments))
 }
 
-#[allow(clippy::too_many_arguments)]
-fn selection_set_field<'a>(
-    alias: &str,
-    field_name: &FieldName,
-    arguments: Vec<(String, Value<'a, String>)>,
-    fields: &Option<NestedField>,
-    field_definition: &ObjectFieldDefinition,
-    parameters: &mut OperationParameters,
-    configuration: &ServerConfig,
-    variables: &BTreeMap<VariableName, serde_json::Value>,
-) -> Result<Selection<'a, String>, QueryBuilderError> {
-    let selection_set = match fields.as_ref().and_then(underlying_fields) {
-        Some(fields) => {
-            let items = fields
-                .iter()
-                .map(|(alias, field)| {
-                    let (field_name, fields, arguments) = match field {
-                        models::Field::Column {
-                            column,
-                            fields,
-                            arguments,
-                        } => (column, fields, arguments),
-                        models::Field::Relationship { .. } => {
-                            return Err(QueryBuilderError::NotSupported(
-                                "Relationships".to_string(),
-                            ))
-                        }
-                    };
-
-                    let object_name = field_definition.r#type.name();
-
-                    // subfield selection should only exist on object types
-                    let field_definition = match configuration.schema.definitions.get(&object_name)
-                    {
-                        Some(TypeDef::Object {
-                            fields,
-                            description: _,
-                        }) => fields.get(field_name).ok_or_else(|| {
-                            QueryBuilderError::ObjectFieldNotFound {
-                                object: field_definition.r#type.name(),
-                                field: field_name.clone(),
+/// Builds the fields of one operation, or of one variable set of a query,
+/// collecting argument values as operation parameters
+struct FieldBuilder<'c> {
+    configuration: &'c ServerConfig,
+    variables: &'c BTreeMap<VariableName, serde_json::Value>,
+    parameters: OperationParameters,
+}
+
+impl<'c> FieldBuilder<'c> {
+    fn new<S: Into<String>>(
+        configuration: &'c ServerConfig,
+        variables: &'c BTreeMap<VariableName, serde_json::Value>,
+        namespace: S,
+    ) -> Self {
+        Self {
+            configuration,
+            variables,
+            parameters: OperationParameters::new(namespace),
+        }
+    }
+    fn root_field<'a>(
+        &mut self,
+        alias: &str,
+        root_field: &RootField,
+        arguments: &Arguments,
+        fields: Option<&NestedField>,
+    ) -> Result<Selection<'a, String>, QueryBuilderError> {
+        let root_type_name = root_type_name(root_field.operation_type, self.configuration)?;
+        let arguments = self.field_arguments(
+            arguments,
+            root_field.definition,
+            &root_field.name,
+            root_type_name,
+        )?;
+
+        self.selection_set_field(
+            alias,
+            &root_field.name,
+            arguments,
+            fields,
+            root_field.definition,
+        )
+    }
+    fn selection_set_field<'a>(
+        &mut self,
+        alias: &str,
+        field_name: &FieldName,
+        arguments: Vec<(String, Value<'a, String>)>,
+        fields: Option<&NestedField>,
+        field_definition: &ObjectFieldDefinition,
+    ) -> Result<Selection<'a, String>, QueryBuilderError> {
+        let selection_set = match fields.and_then(underlying_fields) {
+            Some(fields) => {
+                let items = fields
+                    .iter()
+                    .map(|(alias, field)| {
+                        let (field_name, fields, arguments) = match field {
+                            models::Field::Column {
+                                column,
+                                fields,
+                                arguments,
+                            } => (column, fields, arguments),
+                            models::Field::Relationship { .. } => {
+                                return Err(QueryBuilderError::NotSupported(
+                                    "Relationships".to_string(),
+                                ))
                             }
-                        }),
-                        Some(_) | None => Err(QueryBuilderError::ObjectTypeNotFound(
-                            field_definition.r#type.name(),
-                        )),
-                    }?;
-
-                    selection_set_field(
-                        &alias.to_string(),
-                        field_name,
-                        field_arguments(
-                            arguments,
-                            map_query_arg,
+                        };
+
+                        let object_name = field_definition.r#type.name();
+
+                        // subfield selection should only exist on object types
+                        let field_definition =
+                            match self.configuration.schema.definitions.get(&object_name) {
+                                Some(TypeDef::Object {
+                                    fields,
+                                    description: _,
+                                }) => fields.get(field_name).ok_or_else(|| {
+                                    QueryBuilderError::ObjectFieldNotFound {
+                                        object: field_definition.r#type.name(),
+                                        field: field_name.clone(),
+                                    }
+                                }),
+                                Some(_) | None => Err(QueryBuilderError::ObjectTypeNotFound(
+                                    field_definition.r#type.name(),
+                                )),
+                            }?;
+
+                        let arguments = map_query_arguments(arguments, self.variables)?;
+                        let arguments = self.field_arguments(
+                            &arguments,
                             field_definition,
-                            parameters,
                             field_name,
                             &object_name,
-                            variables,
-                        )?,
-                        fields,
-                        field_definition,
-                        parameters,
-                        configuration,
-                        variables,
-                    )
-                })
-                .collect::<Result<_, _>>()?;
+                        )?;
 
-            SelectionSet {
-                span: (pos(), pos()),
-                items,
-            }
-        }
-        None => SelectionSet {
-            span: (pos(), pos()),
-            items: vec![],
-        },
-    };
-    Ok(Selection::Field(Field {
-        position: pos(),
-        alias: if alias == field_name.inner() {
-            None
-        } else {
-            Some(alias.to_string())
-        },
-        name: field_name.to_string(),
-        arguments,
-        directives: vec![],
-        selection_set,
-    }))
-}
+                        self.selection_set_field(
+                            &alias.to_string(),
+                            field_name,
+                            arguments,
+                            fields.as_ref(),
+                            field_definition,
+                        )
+                    })
+                    .collect::<Result<_, _>>()?;
 
-fn field_arguments<'a, A, M>(
-    arguments: &BTreeMap<ArgumentName, A>,
-    map_argument: M,
-    field_definition: &ObjectFieldDefinition,
-    parameters: &mut OperationParameters,
-    field_name: &FieldName,
-    object_name: &TypeName,
-    variables: &BTreeMap<VariableName, serde_json::Value>,
-) -> Result<Vec<(String, Value<'a, String>)>, QueryBuilderError>
-where
-    M: Fn(
-        &A,
-        &BTreeMap<VariableName, serde_json::Value>,
-    ) -> Result<serde_json::Value, QueryBuilderError>,
-{
-    arguments
-        .iter()
-        .map(|(name, arg)| {
-            let input_type = &field_definition
-                .arguments
-                .get(name)
-                .ok_or(QueryBuilderError::ArgumentNotFound {
+                SelectionSet {
+                    span: (pos(), pos()),
+                    items,
+                }
+            }
+            None => SelectionSet {
+                span: (pos(), pos()),
+                items: vec![],
+            },
+        };
+        Ok(Selection::Field(Field {
+            position: pos(),
+            alias: if alias == field_name.inner() {
+                None
+            } else {
+                Some(alias.to_string())
+            },
+            name: field_name.to_string(),
+            arguments,
+            directives: vec![],
+            selection_set,
+        }))
+    }
+    fn field_arguments<'a>(
+        &mut self,
+        arguments: &Arguments,
+        field_definition: &ObjectFieldDefinition,
+        field_name: &FieldName,
+        object_name: &TypeName,
+    ) -> Result<Vec<(String, Value<'a, String>)>, QueryBuilderError> {
+        let validator = ArgumentValidator::new(
+            &self.configuration.schema.definitions,
+            object_name,
+            field_name,
+        );
+
+        validator.check_required_arguments(field_definition, arguments)?;
+
+        let mut field_arguments = vec![];
+
+        for (name, value) in arguments {
+            let argument_definition = field_definition.arguments.get(name).ok_or(
+                QueryBuilderError::ArgumentNotFound {
                     object: object_name.clone(),
                     field: field_name.clone(),
                     argument: name.clone(),
-                })?
-                .r#type;
+                },
+            )?;
+            let input_type = &argument_definition.r#type;
+
+            // non-null arguments with a default value are advertised as nullable,
+            // so a null value means the argument should be omitted and the default used
+            if value.is_null()
+                && matches!(input_type, TypeRef::NonNull(_))
+                && argument_definition.default_value.is_some()
+            {
+                continue;
+            }
 
-            let value = map_argument(arg, variables)?;
+            let mut value = value.to_owned();
+            validator.validate(name.inner(), &mut value, input_type)?;
 
-            let value = parameters.insert(name, value, input_type);
+            let value = self.parameters.insert(name, value, input_type);
 
-            Ok((name.to_string(), value))
-        })
-        .collect()
-}
+            field_arguments.push((name.to_string(), value));
+        }
 
-fn map_query_arg(
-    argument: &models::Argument,
-    variables: &BTreeMap<VariableName, serde_json::Value>,
-) -> Result<serde_json::Value, QueryBuilderError> {
-    match argument {
-        Argument::Variable { name } => variables
-            .get(name)
-            .map(std::borrow::ToOwned::to_owned)
-            .ok_or_else(|| QueryBuilderError::MissingVariable(name.clone())),
-        Argument::Literal { value } => Ok(value.to_owned()),
+        Ok(field_arguments)
     }
 }
 
-fn map_arg(
-    argument: &serde_json::Value,
-    _variables: &BTreeMap<VariableName, serde_json::Value>,
-) -> Result<serde_json::Value, QueryBuilderError> {
-    Ok(argument.to_owned())
+/// resolve argument values, looking up variables in the given variable set
+fn map_query_arguments(
+    arguments: &BTreeMap<ArgumentName, models::Argument>,
+    variables: &BTreeMap<VariableName, serde_json::Value>,
+) -> Result<Arguments, QueryBuilderError> {
+    arguments
+        .iter()
+        .map(|(name, argument)| {
+            let value = match argument {
+                Argument::Variable { name } => variables
+                    .get(name)
+                    .map(std::borrow::ToOwned::to_owned)
+                    .ok_or_else(|| QueryBuilderError::MissingVariable(name.clone()))?,
+                Argument::Literal { value } => value.to_owned(),
+            };
+            Ok((name.to_owned(), value))
+        })
+        .collect()
 }
 
 fn underlying_fields(nested_field: &NestedField) -> Option<&IndexMap<FieldName, models::Field>> {
@@ -510,6 +540,99 @@ fn underlying_fields(nested_field: &NestedField) -> Option<&IndexMap<FieldName,
     }
 }
 
+/// Add the fields compared by a predicate to the selection of the root field.
+/// Fields are added under their own name, which must not already select another field
+fn select_predicate_fields(
+    mut fields: Option<NestedField>,
+    predicate: &models::Expression,
+) -> Result<Option<NestedField>, QueryBuilderError> {
+    let mut field_paths = vec![];
+    predicate_field_paths(predicate, &mut field_paths);
+
+    for field_path in field_paths {
+        let nested_field = fields.get_or_insert_with(|| {
+            NestedField::Object(models::NestedObject {
+                fields: IndexMap::new(),
+            })
+        });
+        select_field_path(nested_field, field_path)?;
+    }
+
+    Ok(fields)
+}
+
+fn predicate_field_paths<'p>(
+    expression: &'p models::Expression,
+    field_paths: &mut Vec<&'p [FieldName]>,
+) {
+    match expression {
+        models::Expression::And { expressions } | models::Expression::Or { expressions } => {
+            for expression in expressions {
+                predicate_field_paths(expression, field_paths);
+            }
+        }
+        models::Expression::Not { expression } => predicate_field_paths(expression, field_paths),
+        models::Expression::UnaryComparisonOperator { column, .. }
+        | models::Expression::BinaryComparisonOperator { column, .. }
+        | models::Expression::ArrayComparison { column, .. } => {
+            if let models::ComparisonTarget::Column {
+                name,
+                field_path: Some(field_path),
+                ..
+            } = column
+            {
+                if name.as_str() == "__value" && !field_path.is_empty() {
+                    field_paths.push(field_path);
+                }
+            }
+        }
+        models::Expression::Exists { .. } => {}
+    }
+}
+
+fn select_field_path(
+    nested_field: &mut NestedField,
+    field_path: &[FieldName],
+) -> Result<(), QueryBuilderError> {
+    let Some((field_name, rest)) = field_path.split_first() else {
+        return Ok(());
+    };
+    let fields = match nested_field {
+        NestedField::Object(object) => &mut object.fields,
+        NestedField::Array(array) => return select_field_path(&mut array.fields, field_path),
+        NestedField::Collection(_) => {
+            return Err(QueryBuilderError::NotSupported(
+                "Predicates on nested collections".to_string(),
+            ))
+        }
+    };
+
+    let field = fields
+        .entry(field_name.clone())
+        .or_insert_with(|| models::Field::Column {
+            column: field_name.clone(),
+            fields: None,
+            arguments: BTreeMap::new(),
+        });
+
+    match field {
+        models::Field::Column { column, fields, .. } if column == field_name => {
+            if rest.is_empty() {
+                return Ok(());
+            }
+            let fields = fields.get_or_insert_with(|| {
+                NestedField::Object(models::NestedObject {
+                    fields: IndexMap::new(),
+                })
+            });
+            select_field_path(fields, rest)
+        }
+        _ => Err(QueryBuilderError::NotSupported(format!(
+            "Predicates on field {field_name}, when another field is selected as {field_name}"
+        ))),
+    }
+}
+
 /// extract headers from the request_arguments object which is a string map.
 fn extract_headers_from_request_arguments(
     request_arguments: Option<&BTreeMap<ArgumentName, serde_json::Value>>,
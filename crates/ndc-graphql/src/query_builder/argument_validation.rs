@@ -0,0 +1,268 @@
+use super::error::{ArgumentValueError, QueryBuilderError};
+use common::config::schema::{ObjectFieldDefinition, TypeDef, TypeRef};
+use ndc_sdk::models::{ArgumentName, FieldName, TypeName};
+use std::collections::BTreeMap;
+
+/// Validates argument values for a single field against the schema definition,
+/// so invalid requests are rejected with an actionable error before being sent upstream.
+pub struct ArgumentValidator<'a> {
+    definitions: &'a BTreeMap<TypeName, TypeDef>,
+    object: &'a TypeName,
+    field: &'a FieldName,
+}
+
+impl<'a> ArgumentValidator<'a> {
+    pub fn new(
+        definitions: &'a BTreeMap<TypeName, TypeDef>,
+        object: &'a TypeName,
+        field: &'a FieldName,
+    ) -> Self {
+        Self {
+            definitions,
+            object,
+            field,
+        }
+    }
+    /// all non-null arguments without a default value must be present
+    pub fn check_required_arguments<A>(
+        &self,
+        field_definition: &ObjectFieldDefinition,
+        arguments: &BTreeMap<ArgumentName, A>,
+    ) -> Result<(), QueryBuilderError> {
+        for (name, argument) in &field_definition.arguments {
+            if argument.is_required() && !arguments.contains_key(name) {
+                return Err(QueryBuilderError::MissingArgument {
+                    object: self.object.clone(),
+                    field: self.field.clone(),
+                    argument: name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+    /// Check the value matches the type. Null fields of oneOf input objects are removed,
+    /// as the upstream would count them as set. So are null non-null fields with a default value,
+    /// which are advertised as nullable, so the default is used
+    pub fn validate(
+        &self,
+        path: &str,
+        value: &mut serde_json::Value,
+        r#type: &TypeRef,
+    ) -> Result<(), QueryBuilderError> {
+        match (r#type, value) {
+            (TypeRef::NonNull(_), serde_json::Value::Null) => Err(self.error(
+                path,
+                ArgumentValueError::UnexpectedNull {
+                    expected: r#type.to_string(),
+                },
+            )),
+            (TypeRef::NonNull(underlying), value) => self.validate(path, value, underlying),
+            (_, serde_json::Value::Null) => Ok(()),
+            (TypeRef::List(underlying), serde_json::Value::Array(items)) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.validate(&format!("{path}[{index}]"), item, underlying)?;
+                }
+                Ok(())
+            }
+            // per the GraphQL spec, input coercion accepts a single value in place of a list
+            (TypeRef::List(underlying), value) => self.validate(path, value, underlying),
+            (TypeRef::Named(name), value) => self.validate_named(path, value, name),
+        }
+    }
+    fn validate_named(
+        &self,
+        path: &str,
+        value: &mut serde_json::Value,
+        name: &str,
+    ) -> Result<(), QueryBuilderError> {
+        match self.definitions.get(name) {
+            // scalar values are opaque to us, and checked by the upstream
+            Some(TypeDef::Scalar { .. }) => Ok(()),
+            Some(TypeDef::Enum { values, .. }) => match value {
+                serde_json::Value::String(value)
+                    if values.iter().any(|enum_value| &enum_value.name == value) =>
+                {
+                    Ok(())
+                }
+                _ => Err(self.error(
+                    path,
+                    ArgumentValueError::UnknownEnumValue {
+                        enum_type: name.to_owned().into(),
+                        value: value.clone(),
+                    },
+                )),
+            },
+            Some(TypeDef::InputObject { fields, one_of, .. }) => {
+                let serde_json::Value::Object(object) = value else {
+                    return Err(self.error(
+                        path,
+                        ArgumentValueError::ExpectedInputObject {
+                            input_object: name.to_owned().into(),
+                            value: value.clone(),
+                        },
+                    ));
+                };
+
+                object.retain(|field_name, field_value| {
+                    let defaulted =
+                        fields
+                            .get(field_name.as_str())
+                            .is_some_and(|field_definition| {
+                                matches!(field_definition.r#type, TypeRef::NonNull(_))
+                                    && field_definition.default_value.is_some()
+                            });
+                    !(defaulted && field_value.is_null())
+                });
+
+                for (field_name, field_value) in object.iter_mut() {
+                    let field_definition = fields.get(field_name.as_str()).ok_or_else(|| {
+                        self.error(
+                            path,
+                            ArgumentValueError::InputObjectFieldNotFound {
+                                input_object: name.to_owned().into(),
+                                field: field_name.to_owned(),
+                            },
+                        )
+                    })?;
+
+                    self.validate(
+                        &format!("{path}.{field_name}"),
+                        field_value,
+                        &field_definition.r#type,
+                    )?;
+                }
+
+                for (field_name, field_definition) in fields {
+                    if field_definition.is_required() && !object.contains_key(field_name.as_str()) {
+                        return Err(self.error(
+                            path,
+                            ArgumentValueError::MissingInputObjectField {
+                                input_object: name.to_owned().into(),
+                                field: field_name.to_string(),
+                                expected: field_definition.r#type.to_string(),
+                            },
+                        ));
+                    }
+                }
+
+                // per the GraphQL spec, oneOf input objects must have exactly one key, with a non-null value.
+                // clients commonly send unset fields as null, so those are left out instead
+                if *one_of {
+                    object.retain(|_, field_value| !field_value.is_null());
+
+                    if object.len() != 1 {
+                        return Err(self.error(
+                            path,
+                            ArgumentValueError::OneOfInputObject {
+                                input_object: name.to_owned().into(),
+                                fields: object.keys().cloned().collect(),
+                            },
+                        ));
+                    }
+                }
+
+                Ok(())
+            }
+            Some(TypeDef::Object { .. }) | None => Err(QueryBuilderError::InputObjectTypeNotFound(
+                name.to_owned().into(),
+            )),
+        }
+    }
+    fn error(&self, path: &str, error: ArgumentValueError) -> QueryBuilderError {
+        QueryBuilderError::InvalidArgumentValue {
+            object: self.object.clone(),
+            field: self.field.clone(),
+            path: path.to_owned(),
+            error: Box::new(error),
+        }
+    }
+}
+
+#[test]
+fn test_one_of_input_object() {
+    use common::config::{
+        schema::{parse_schema, SchemaDefinition},
+        SchemaConfig,
+    };
+
+    let schema_document = parse_schema(
+        r"
+        type Query { user(by: UserBy!): String }
+        input UserBy @oneOf { id: ID, email: String }
+        ",
+    )
+    .expect("schema should parse");
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect("schema should be valid");
+
+    let object = TypeName::from("Query");
+    let field = FieldName::from("user");
+    let validator = ArgumentValidator::new(&schema.definitions, &object, &field);
+    let r#type = &schema.query_fields["user"].arguments["by"].r#type;
+
+    for (mut value, expected) in [
+        (
+            serde_json::json!({ "id": "1" }),
+            serde_json::json!({ "id": "1" }),
+        ),
+        (
+            serde_json::json!({ "id": "1", "email": null }),
+            serde_json::json!({ "id": "1" }),
+        ),
+    ] {
+        assert!(validator.validate("by", &mut value, r#type).is_ok());
+        assert_eq!(value, expected);
+    }
+
+    for (mut value, expected) in [
+        (serde_json::json!({}), "got none"),
+        (serde_json::json!({ "id": null }), "got none"),
+        (
+            serde_json::json!({ "id": "1", "email": "a@example.com" }),
+            "got id, email",
+        ),
+    ] {
+        let error = validator
+            .validate("by", &mut value, r#type)
+            .expect_err("value should be invalid")
+            .to_string();
+        assert!(error.ends_with(expected), "{error}");
+    }
+}
+
+#[test]
+fn test_defaulted_input_object_fields() {
+    use common::config::{
+        schema::{parse_schema, SchemaDefinition},
+        SchemaConfig,
+    };
+
+    let schema_document = parse_schema(
+        r"
+        type Query { users(page: Page!): String }
+        input Page { size: Int! = 20, after: String! }
+        ",
+    )
+    .expect("schema should parse");
+    let schema = SchemaDefinition::new(&schema_document, &SchemaConfig::default())
+        .expect("schema should be valid");
+
+    let object = TypeName::from("Query");
+    let field = FieldName::from("users");
+    let validator = ArgumentValidator::new(&schema.definitions, &object, &field);
+    let r#type = &schema.query_fields["users"].arguments["page"].r#type;
+
+    // null non-null fields with a default are left out, so the upstream uses the default
+    let mut value = serde_json::json!({ "size": null, "after": "a" });
+    assert!(validator.validate("page", &mut value, r#type).is_ok());
+    assert_eq!(value, serde_json::json!({ "after": "a" }));
+
+    let mut value = serde_json::json!({ "size": 10, "after": null });
+    let error = validator
+        .validate("page", &mut value, r#type)
+        .expect_err("null value for a required field should be invalid");
+    assert!(matches!(
+        error,
+        QueryBuilderError::InvalidArgumentValue { ref path, .. } if path == "page.after"
+    ));
+}
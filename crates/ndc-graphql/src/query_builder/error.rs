@@ -33,6 +33,17 @@ pub enum QueryBuilderError {
         field: FieldName,
         argument: ArgumentName,
     },
+    MissingArgument {
+        object: TypeName,
+        field: FieldName,
+        argument: ArgumentName,
+    },
+    InvalidArgumentValue {
+        object: TypeName,
+        field: FieldName,
+        path: String,
+        error: Box<ArgumentValueError>,
+    },
     MisshapenHeadersArgument(serde_json::Value),
     Unexpected(String),
     MissingVariable(VariableName),
@@ -40,6 +51,35 @@ pub enum QueryBuilderError {
 
 impl std::error::Error for QueryBuilderError {}
 
+/// Reasons an argument value does not match the argument type
+#[derive(Debug)]
+pub enum ArgumentValueError {
+    UnexpectedNull {
+        expected: String,
+    },
+    UnknownEnumValue {
+        enum_type: TypeName,
+        value: serde_json::Value,
+    },
+    ExpectedInputObject {
+        input_object: TypeName,
+        value: serde_json::Value,
+    },
+    InputObjectFieldNotFound {
+        input_object: TypeName,
+        field: String,
+    },
+    MissingInputObjectField {
+        input_object: TypeName,
+        field: String,
+        expected: String,
+    },
+    OneOfInputObject {
+        input_object: TypeName,
+        fields: Vec<String>,
+    },
+}
+
 impl From<QueryBuilderError> for QueryError {
     fn from(value: QueryBuilderError) -> Self {
         QueryError::new_invalid_request(&value)
@@ -91,6 +131,23 @@ impl Display for QueryBuilderError {
                 f,
                 "Argument {argument} for field {field} not found in Object Type {object}"
             ),
+            QueryBuilderError::MissingArgument {
+                object,
+                field,
+                argument,
+            } => write!(
+                f,
+                "Missing required argument {argument} for field {field} in Object Type {object}"
+            ),
+            QueryBuilderError::InvalidArgumentValue {
+                object,
+                field,
+                path,
+                error,
+            } => write!(
+                f,
+                "Invalid value for argument {path} of field {field} in Object Type {object}: {error}"
+            ),
             QueryBuilderError::Unexpected(s) => write!(f, "Unexpected: {s}"),
             QueryBuilderError::QueryFieldNotFound { field } => {
                 write!(f, "Field {field} not found in Query type")
@@ -105,3 +162,46 @@ impl Display for QueryBuilderError {
         }
     }
 }
+
+impl Display for ArgumentValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentValueError::UnexpectedNull { expected } => {
+                write!(f, "expected non-null value of type {expected}, got null")
+            }
+            ArgumentValueError::UnknownEnumValue { enum_type, value } => {
+                write!(f, "{value} is not a value of Enum Type {enum_type}")
+            }
+            ArgumentValueError::ExpectedInputObject {
+                input_object,
+                value,
+            } => write!(
+                f,
+                "expected object for Input Object Type {input_object}, got {value}"
+            ),
+            ArgumentValueError::InputObjectFieldNotFound {
+                input_object,
+                field,
+            } => write!(
+                f,
+                "Field {field} not found in Input Object Type {input_object}"
+            ),
+            ArgumentValueError::MissingInputObjectField {
+                input_object,
+                field,
+                expected,
+            } => write!(
+                f,
+                "missing required field {field} of type {expected} in Input Object Type {input_object}"
+            ),
+            ArgumentValueError::OneOfInputObject {
+                input_object,
+                fields,
+            } => write!(
+                f,
+                "exactly one field of oneOf Input Object Type {input_object} must be set to a non-null value, got {}",
+                if fields.is_empty() { "none".to_owned() } else { fields.join(", ") }
+            ),
+        }
+    }
+}
@@ -3,29 +3,38 @@ use common::config::ServerConfig;
 use common::{config::config_file::ServerConfigFile, schema_response::schema_response};
 use insta::{assert_json_snapshot, assert_snapshot, assert_yaml_snapshot, glob};
 use ndc_graphql::{
-    connector::setup::GraphQLConnectorSetup,
+    connector::{setup::GraphQLConnectorSetup, GraphQLConnector},
     query_builder::{build_mutation_document, build_query_document},
 };
-use ndc_sdk::models;
+use ndc_sdk::{
+    connector::{Connector, ConnectorSetup},
+    json_response::JsonResponse,
+    models,
+};
 use schemars::schema_for;
 use std::{collections::HashMap, fs, path::PathBuf};
 
 #[tokio::test]
-#[ignore]
+#[ignore = "regenerates the JSON schema files used by test requests and configuration"]
 async fn update_json_schema() {
-    for config in ["config-1", "config-2", "config-3"] {
+    for config in [
+        "config-1", "config-2", "config-3", "config-4", "config-5", "config-6",
+    ] {
         fs::write(
             format!("./tests/{config}/queries/_query_request.schema.json"),
             serde_json::to_string_pretty(&schema_for!(models::QueryRequest))
                 .expect("Should serialize schema to json"),
         )
         .expect("Should be able to write out schema file");
-        fs::write(
-            format!("./tests/{config}/mutations/_mutation_request.schema.json"),
-            serde_json::to_string_pretty(&schema_for!(models::MutationRequest))
-                .expect("Should serialize schema to json"),
-        )
-        .expect("Should be able to write out schema file");
+        // some configurations only have queries
+        if PathBuf::from(format!("./tests/{config}/mutations")).exists() {
+            fs::write(
+                format!("./tests/{config}/mutations/_mutation_request.schema.json"),
+                serde_json::to_string_pretty(&schema_for!(models::MutationRequest))
+                    .expect("Should serialize schema to json"),
+            )
+            .expect("Should be able to write out schema file");
+        }
         fs::write(
             format!("./tests/{config}/configuration/configuration.schema.json"),
             serde_json::to_string_pretty(&schema_for!(ServerConfigFile))
@@ -57,7 +66,9 @@ async fn read_configuration(config: &str) -> ServerConfig {
 
 #[tokio::test]
 async fn test_build_graphql_query() {
-    for config in ["config-1", "config-2", "config-3"] {
+    for config in [
+        "config-1", "config-2", "config-3", "config-4", "config-5", "config-6",
+    ] {
         let configuration = read_configuration(config).await;
 
         glob!(format!("./{config}/queries"), "*.request.json", |path| {
@@ -74,9 +85,25 @@ async fn test_build_graphql_query() {
     }
 }
 
+#[tokio::test]
+async fn test_invalid_graphql_query() {
+    let configuration = read_configuration("config-1").await;
+
+    glob!("./config-1/invalid_queries", "*.request.json", |path| {
+        let request = fs::read_to_string(path).expect("Should be able to read file");
+        let request: models::QueryRequest =
+            serde_json::from_str(&request).expect("Should be valid request json");
+        let error = build_query_document(&request, &configuration)
+            .err()
+            .expect("Should fail to build query document");
+
+        assert_snapshot!("Query Error", error.to_string());
+    });
+}
+
 #[tokio::test]
 async fn test_build_graphql_mutation() {
-    for config in ["config-1", "config-2", "config-3"] {
+    for config in ["config-1", "config-2", "config-3", "config-4", "config-6"] {
         let configuration = read_configuration(config).await;
 
         glob!(format!("./{config}/mutations"), "*.request.json", |path| {
@@ -95,17 +122,96 @@ async fn test_build_graphql_mutation() {
 
 #[tokio::test]
 async fn test_generated_schema() {
-    for config in ["config-1", "config-2", "config-3"] {
+    for config in [
+        "config-1", "config-2", "config-3", "config-4", "config-5", "config-6",
+    ] {
         let configuration = read_configuration(config).await;
         let schema = schema_response(
             &configuration.schema,
             &configuration.request,
             &configuration.response,
+            &configuration.naming,
         );
         assert_yaml_snapshot!(format!("{config} NDC Schema"), schema);
     }
 }
 
+// the glob callback is synchronous, so queries block on the runtime
+#[tokio::test(flavor = "multi_thread")]
+async fn test_query_predicates() {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    // stand-in upstream, responding to each request with the same account
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/graphql", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = serde_json::json!({
+                "data": {
+                    "__value": {
+                        "id": "1",
+                        "balance": "100000000000000000000.25",
+                        "openedAt": "2024-03-01T12:00:00+02:00",
+                        "tags": ["checking"]
+                    }
+                }
+            })
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+        }
+    });
+
+    let mut configuration = read_configuration("config-5").await;
+    configuration.connection.endpoint = endpoint;
+    let state = GraphQLConnectorSetup::new(HashMap::new())
+        .try_init_state(&configuration, &mut prometheus::Registry::new())
+        .await
+        .expect("Should initialize state");
+
+    glob!("./config-5/queries", "*.request.json", |path| {
+        let request = fs::read_to_string(path).expect("Should be able to read file");
+        let request: models::QueryRequest =
+            serde_json::from_str(&request).expect("Should be valid request json");
+        let response = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(GraphQLConnector::query(
+                &configuration,
+                &state,
+                request,
+            ))
+        })
+        .expect("Should successfully run query");
+        let JsonResponse::Value(response) = response else {
+            panic!("Should respond with a value");
+        };
+
+        assert_json_snapshot!("Query Response", response);
+    });
+}
+
 #[test]
 fn test_capabilities() {
     assert_yaml_snapshot!("Capabilities", capabilities_response());